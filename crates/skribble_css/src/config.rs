@@ -1,27 +1,49 @@
+use std::path::Path;
+
 use heck::ToKebabCase;
 use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 
-use crate::constants::JSON_CONFIG;
+use crate::{constants::JSON_CONFIG, utils::prefix_css_variable};
 
 use self::{
-  color_utils::{get_rgba_color_from_string, wrap_css_variable},
+  color_expression::ColorResolver,
+  color_utils::{get_color_from_string, wrap_css_variable},
   user::{
-    Atom, AtomColor, AtomColorOptions, AtomValue, CssValue, CssVariable, PopulatedCssVariable,
+    Atom, AtomColor, AtomColorOptions, AtomFluid, AtomScale, AtomValue, BreakpointHelper, CssValue,
+    CssVariable, Options, PopulatedCssVariable,
   },
 };
+pub use design_tokens::{DesignTokens, SkippedToken};
+pub use error::{ConfigError, ConfigErrorKind};
+pub use extends::{ListMerge, MapMerge, MergeOptions};
+pub use format::ConfigFormat;
 pub use user::UserConfig;
+pub use validate::{ConfigIssue, Severity};
+pub(crate) mod color_expression;
 pub(crate) mod color_utils;
+pub(crate) mod css_color;
+pub(crate) mod palette;
 
+pub mod design_tokens;
+pub mod error;
+pub mod extends;
+pub mod format;
 pub mod user;
+pub mod validate;
 
 /// The built in configuration
 #[derive(Serialize, Deserialize, Debug, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct Config {
-  /// The user configuration.
+  /// The user configuration with the `variablesPrefix` applied to every css
+  /// variable.
   pub user: UserConfig,
 
+  /// All the breakpoint names, including the generated `max` and `Only`
+  /// variants, with their media query.
+  pub breakpoints: IndexMap<String, String>,
+
   pub modifiers_map: IndexMap<String, Vec<String>>,
 
   /// All the modifier names.
@@ -37,11 +59,22 @@ pub struct Config {
 type AtomMap = IndexMap<String, IndexMap<String, CssValue>>;
 
 impl Config {
-  pub fn new(source: &str) -> serde_json::Result<Self> {
-    let user = UserConfig::new(source)?;
+  pub fn new(source: &str) -> Result<Self, ConfigError> {
+    Self::from_user(UserConfig::new(source)?)
+  }
+
+  /// Load the configuration from a file in any supported `ConfigFormat`.
+  pub fn from_path(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+    Self::from_user(UserConfig::from_path(path)?)
+  }
+
+  pub fn from_user(mut user: UserConfig) -> Result<Self, ConfigError> {
+    user.apply_variables_prefix();
+
     let mut modifiers_map: IndexMap<String, Vec<String>> = IndexMap::new();
     let mut css_variables: IndexMap<String, PopulatedCssVariable> = IndexMap::new();
     let palette = user.palette.to_map();
+    let resolved_colors = ColorResolver::new(&palette, &user.colors).resolve_colors()?;
 
     let modifiers: Vec<String> = user
       .modifiers
@@ -68,8 +101,14 @@ impl Config {
           style_rules,
         }) => {
           for rule in style_rules {
-            let values: IndexMap<String, CssValue> =
-              values_from_color_options(rule, colors, &user.colors, &palette, &mut css_variables);
+            let values: IndexMap<String, CssValue> = values_from_color_options(
+              rule,
+              colors,
+              &resolved_colors,
+              &palette,
+              &user.options,
+              &mut css_variables,
+            );
 
             match atoms.get_mut(rule) {
               Some(atom) => {
@@ -92,38 +131,58 @@ impl Config {
         Atom::Value(AtomValue {
           style_rules,
           values,
-        }) => {
-          for rule in style_rules {
-            match atoms.get_mut(rule) {
-              Some(atom) => {
-                for (key, value) in values {
-                  atom.insert(key.to_owned(), value.to_owned());
-                }
-              }
-              None => {
-                let mut atom = IndexMap::new();
-
-                for (key, value) in values {
-                  atom.insert(key.to_owned(), value.to_owned());
-                }
-
-                atoms.insert(rule.to_owned(), atom);
-              }
-            };
-          }
+        }) => insert_atom_values(&mut atoms, style_rules, values),
+        Atom::Scale(AtomScale { style_rules, scale }) => {
+          insert_atom_values(&mut atoms, style_rules, &scale.to_values())
+        }
+        Atom::Fluid(AtomFluid { style_rules, fluid }) => {
+          insert_atom_values(&mut atoms, style_rules, &fluid.to_values(&user.breakpoints))
         }
       }
     }
 
-    let config = Self {
+    let breakpoints = user
+      .breakpoints
+      .to_breakpoints()
+      .into_iter()
+      .filter_map(|(name, query)| name.map(|name| (name, query)))
+      .collect();
+
+    Ok(Self {
       user,
+      breakpoints,
       modifiers,
       modifiers_map,
       atoms,
       css_variables,
-    };
+    })
+  }
+}
+
+/// Add the values to every style rule. A key which is already defined by an
+/// earlier atom for the same style rule is replaced by the later value.
+fn insert_atom_values(
+  atoms: &mut AtomMap,
+  style_rules: &[String],
+  values: &IndexMap<String, CssValue>,
+) {
+  for rule in style_rules {
+    match atoms.get_mut(rule) {
+      Some(atom) => {
+        for (key, value) in values {
+          atom.insert(key.to_owned(), value.to_owned());
+        }
+      }
+      None => {
+        let mut atom = IndexMap::new();
+
+        for (key, value) in values {
+          atom.insert(key.to_owned(), value.to_owned());
+        }
 
-    Ok(config)
+        atoms.insert(rule.to_owned(), atom);
+      }
+    };
   }
 }
 
@@ -132,22 +191,27 @@ fn values_from_color_options(
   options: &AtomColorOptions,
   colors: &IndexMap<String, CssVariable>,
   palette: &IndexMap<String, String>,
+  config_options: &Options,
   css_variables: &mut IndexMap<String, PopulatedCssVariable>,
 ) -> IndexMap<String, CssValue> {
   let mut values: IndexMap<String, CssValue> = IndexMap::new();
+  let format = config_options.color_format;
 
   if options.palette {
     for (name, value) in palette.iter() {
       values.insert(
         name.to_owned(),
-        CssValue::String(get_rgba_color_from_string(value, &options.opacity)),
+        CssValue::String(get_color_from_string(value, &options.opacity, format)),
       );
     }
   }
 
   for (name, css_variable) in colors.iter() {
-    let populated_css_variable = css_variable.populate_color(palette, &options.opacity);
-    let css_variable_name = format!("--color-{}-{}", rule.to_kebab_case(), name.to_kebab_case());
+    let populated_css_variable = css_variable.populate_color(palette, &options.opacity, format);
+    let css_variable_name = prefix_css_variable(
+      &format!("--color-{}-{}", rule.to_kebab_case(), name.to_kebab_case()),
+      &config_options.variables_prefix,
+    );
     values.insert(
       name.clone(),
       CssValue::String(wrap_css_variable(&css_variable_name)),
@@ -166,10 +230,75 @@ impl Default for Config {
 
 #[cfg(test)]
 mod tests {
-  use super::Config;
+  use super::{Config, ConfigErrorKind};
+  use crate::generate_typescript::generate_typescript;
 
   #[test]
   fn can_create_config() {
     Config::default();
   }
+
+  #[test]
+  fn scale_atoms_match_listed_values() {
+    let scale = Config::new(
+      r#"{ "extends": "default", "merge": { "atoms": "override" }, "atoms": [
+        { "styleRules": ["p"], "scale": { "start": 0, "end": 2, "multiplier": 0.25, "unit": "rem" } }
+      ] }"#,
+    )
+    .unwrap();
+    let listed = Config::new(
+      r#"{ "extends": "default", "merge": { "atoms": "override" }, "atoms": [
+        { "styleRules": ["p"], "values": { "0": "0rem", "1": "0.25rem", "2": "0.5rem" } }
+      ] }"#,
+    )
+    .unwrap();
+
+    pretty_assertions::assert_eq!(scale.atoms, listed.atoms);
+    pretty_assertions::assert_eq!(generate_typescript(&scale), generate_typescript(&listed));
+  }
+
+  #[test]
+  fn later_atoms_replace_values_with_the_same_key() {
+    let config = Config::new(
+      r#"{ "extends": "default", "atoms": [
+        { "styleRules": ["p"], "values": { "1": "2px", "2": "4px" } },
+        { "styleRules": ["p"], "values": { "1": "1px" } }
+      ] }"#,
+    )
+    .unwrap();
+
+    pretty_assertions::assert_eq!(config.atoms["p"]["1"].get_string(), "1px");
+    pretty_assertions::assert_eq!(config.atoms["p"]["2"].get_string(), "4px");
+  }
+
+  #[test]
+  fn circular_color_expressions_fail_to_load() {
+    let error = Config::new(
+      r#"{ "extends": "default", "colors": {
+        "primary": "mix(secondary, white)",
+        "secondary": "alpha(primary, 0.5)"
+      } }"#,
+    )
+    .unwrap_err();
+
+    pretty_assertions::assert_eq!(error.kind, ConfigErrorKind::Data);
+    pretty_assertions::assert_eq!(error.path, "/colors/primary");
+    pretty_assertions::assert_eq!(
+      error.message,
+      "circular color reference: primary -> secondary -> primary"
+    );
+  }
+
+  #[test]
+  fn unresolved_color_expressions_fail_to_load() {
+    let error =
+      Config::new(r#"{ "extends": "default", "colors": { "loopA": "darken(loopB, 10%)" } }"#)
+        .unwrap_err();
+
+    pretty_assertions::assert_eq!(error.path, "/colors/loopA");
+    pretty_assertions::assert_eq!(
+      error.message,
+      "`loopB` is not a css color, a palette name or an entry in `colors`."
+    );
+  }
 }
@@ -0,0 +1,491 @@
+use colorsys::{ColorAlpha, Rgb};
+use indexmap::IndexMap;
+
+use crate::constants::ROOT_SELECTOR;
+
+use super::{
+  color_utils::{fit_to_srgb, get_hex_string, parse_rgb, rgb_to_oklch},
+  error::ConfigError,
+  user::{CssValue, CssVariable, CssVariableSelectors, PopulatedCssVariable},
+};
+
+const FUNCTIONS: [&str; 4] = ["mix", "alpha", "lighten", "darken"];
+
+/// A color value which is computed while the configuration is built.
+///
+/// - `mix(purple500, white, 20%)` mixes `20%` of the second color into the
+///   first. The amount defaults to `50%`.
+/// - `alpha(sky500, 0.5)` sets the alpha channel.
+/// - `lighten(primary, 10%)` and `darken(primary, 10%)` change the `OKLCH`
+///   lightness.
+///
+/// Amounts are either percentages (`20%`) or fractions (`0.2`).
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum ColorExpression {
+  /// A css color, a palette name or the name of another entry in `colors`.
+  Color(String),
+  Mix(Box<ColorExpression>, Box<ColorExpression>, f64),
+  Alpha(Box<ColorExpression>, f64),
+  Lighten(Box<ColorExpression>, f64),
+  Darken(Box<ColorExpression>, f64),
+}
+
+impl ColorExpression {
+  pub(crate) fn parse(value: &str) -> Result<Self, String> {
+    let mut parser = Parser {
+      input: value,
+      position: 0,
+    };
+    let expression = parser.expression()?;
+    parser.skip_whitespace();
+
+    if parser.position < value.len() {
+      return Err(format!(
+        "unexpected `{}` in `{}`.",
+        &value[parser.position..],
+        value
+      ));
+    }
+
+    Ok(expression)
+  }
+}
+
+struct Parser<'a> {
+  input: &'a str,
+  position: usize,
+}
+
+impl Parser<'_> {
+  fn rest(&self) -> &str {
+    &self.input[self.position..]
+  }
+
+  fn skip_whitespace(&mut self) {
+    let rest = self.rest();
+    self.position += rest.len() - rest.trim_start().len();
+  }
+
+  fn expect(&mut self, character: char) -> Result<(), String> {
+    self.skip_whitespace();
+
+    if self.rest().starts_with(character) {
+      self.position += 1;
+      Ok(())
+    } else {
+      Err(format!("expected `{}` in `{}`.", character, self.input))
+    }
+  }
+
+  fn expression(&mut self) -> Result<ColorExpression, String> {
+    self.skip_whitespace();
+    let start = self.position;
+    let length = self
+      .rest()
+      .find(|character: char| {
+        !(character.is_alphanumeric() || matches!(character, '#' | '-' | '_' | '.'))
+      })
+      .unwrap_or_else(|| self.rest().len());
+
+    if length == 0 {
+      return Err(format!("expected a color in `{}`.", self.input));
+    }
+
+    self.position += length;
+    let name = &self.input[start..self.position];
+
+    if !self.rest().starts_with('(') {
+      return Ok(ColorExpression::Color(name.to_owned()));
+    }
+
+    if !FUNCTIONS.contains(&name) {
+      // Any other function is a css color like `rgb(0, 0, 0)`.
+      self.skip_function()?;
+      return Ok(ColorExpression::Color(
+        self.input[start..self.position].to_owned(),
+      ));
+    }
+
+    self.expect('(')?;
+    let color = Box::new(self.expression()?);
+
+    let expression = match name {
+      "mix" => {
+        self.expect(',')?;
+        let other = Box::new(self.expression()?);
+        let amount = self.optional_amount()?.unwrap_or(0.5);
+        ColorExpression::Mix(color, other, amount)
+      }
+      "alpha" => ColorExpression::Alpha(color, self.amount()?),
+      "lighten" => ColorExpression::Lighten(color, self.amount()?),
+      _ => ColorExpression::Darken(color, self.amount()?),
+    };
+
+    self.expect(')')?;
+    Ok(expression)
+  }
+
+  /// Move past the arguments of a function, including nested functions.
+  fn skip_function(&mut self) -> Result<(), String> {
+    let mut depth = 0;
+
+    for (index, character) in self.rest().char_indices() {
+      match character {
+        '(' => depth += 1,
+        ')' => depth -= 1,
+        _ => continue,
+      }
+
+      if depth == 0 {
+        self.position += index + 1;
+        return Ok(());
+      }
+    }
+
+    Err(format!("missing `)` in `{}`.", self.input))
+  }
+
+  fn amount(&mut self) -> Result<f64, String> {
+    self
+      .optional_amount()?
+      .ok_or_else(|| format!("expected an amount in `{}`.", self.input))
+  }
+
+  fn optional_amount(&mut self) -> Result<Option<f64>, String> {
+    self.skip_whitespace();
+
+    if !self.rest().starts_with(',') {
+      return Ok(None);
+    }
+
+    self.position += 1;
+    let length = self.rest().find(')').unwrap_or_else(|| self.rest().len());
+    let raw = self.rest()[..length].trim().to_owned();
+    self.position += length;
+
+    let amount = match raw.strip_suffix('%') {
+      Some(percentage) => percentage.trim().parse::<f64>().map(|value| value / 100.0),
+      None => raw.parse::<f64>(),
+    };
+
+    match amount {
+      Ok(amount) if (0.0..=1.0).contains(&amount) => Ok(Some(amount)),
+      _ => Err(format!(
+        "`{}` is not an amount between `0%` and `100%`.",
+        raw
+      )),
+    }
+  }
+}
+
+/// Evaluates the color expressions in `colors` using the palette and the other
+/// entries in `colors`.
+pub(crate) struct ColorResolver<'a> {
+  palette: &'a IndexMap<String, String>,
+  colors: &'a IndexMap<String, CssVariable>,
+}
+
+impl<'a> ColorResolver<'a> {
+  pub(crate) fn new(
+    palette: &'a IndexMap<String, String>,
+    colors: &'a IndexMap<String, CssVariable>,
+  ) -> Self {
+    Self { palette, colors }
+  }
+
+  /// Replace every expression in `colors` with the computed color. Fails when
+  /// an expression is circular or refers to a color which doesn't exist so that
+  /// unevaluated expressions never end up in the css.
+  pub(crate) fn resolve_colors(&self) -> Result<IndexMap<String, CssVariable>, ConfigError> {
+    self
+      .colors
+      .iter()
+      .map(|(name, variable)| {
+        let resolved = self
+          .resolve_variable(name, variable)
+          .map_err(|message| ConfigError::data(&["colors".into(), name.to_owned()], &message))?;
+
+        Ok((name.to_owned(), resolved))
+      })
+      .collect()
+  }
+
+  fn resolve_variable(&self, name: &str, variable: &CssVariable) -> Result<CssVariable, String> {
+    let resolved = match variable {
+      CssVariable::Value(value) => {
+        CssVariable::Value(self.resolve_css_value(name, ROOT_SELECTOR, value)?)
+      }
+      CssVariable::Object(object) => {
+        let resolve_parent = |parent: &IndexMap<String, CssVariableSelectors>| {
+          parent
+            .iter()
+            .map(|(key, selectors)| Ok((key.to_owned(), self.resolve_selectors(name, selectors)?)))
+            .collect::<Result<_, String>>()
+        };
+
+        CssVariable::Object(PopulatedCssVariable {
+          selectors: self.resolve_selectors(name, &object.selectors)?,
+          media_queries: object
+            .media_queries
+            .as_ref()
+            .map(resolve_parent)
+            .transpose()?,
+          breakpoints: object
+            .breakpoints
+            .as_ref()
+            .map(resolve_parent)
+            .transpose()?,
+          themes: object
+            .themes
+            .as_ref()
+            .map(|themes| self.resolve_selectors(name, themes))
+            .transpose()?,
+        })
+      }
+    };
+
+    Ok(resolved)
+  }
+
+  fn resolve_selectors(
+    &self,
+    name: &str,
+    selectors: &CssVariableSelectors,
+  ) -> Result<CssVariableSelectors, String> {
+    selectors
+      .iter()
+      .map(|(selector, value)| {
+        Ok((
+          selector.to_owned(),
+          self.resolve_css_value(name, selector, value)?,
+        ))
+      })
+      .collect()
+  }
+
+  fn resolve_css_value(
+    &self,
+    name: &str,
+    selector: &str,
+    value: &CssValue,
+  ) -> Result<CssValue, String> {
+    let resolved = match self.resolve(name, selector, &value.get_string())? {
+      Some(color) => CssValue::String(color),
+      None => value.clone(),
+    };
+
+    Ok(resolved)
+  }
+
+  /// Compute the value of the `colors` entry for the selector. Returns `None`
+  /// when the value is not an expression or a reference to another entry.
+  pub(crate) fn resolve(
+    &self,
+    name: &str,
+    selector: &str,
+    value: &str,
+  ) -> Result<Option<String>, String> {
+    let expression = match ColorExpression::parse(value) {
+      Ok(ColorExpression::Color(color)) if !self.is_reference(&color) => return Ok(None),
+      Ok(expression) => expression,
+      Err(_) if !FUNCTIONS.iter().any(|function| value.starts_with(function)) => return Ok(None),
+      Err(error) => return Err(error),
+    };
+
+    let rgb = self.evaluate(&expression, selector, &mut vec![name.to_owned()])?;
+    Ok(Some(get_hex_string(&rgb, true)))
+  }
+
+  /// Palette names take precedence over the entries in `colors`.
+  fn is_reference(&self, name: &str) -> bool {
+    !self.palette.contains_key(name) && self.colors.contains_key(name)
+  }
+
+  fn evaluate(
+    &self,
+    expression: &ColorExpression,
+    selector: &str,
+    stack: &mut Vec<String>,
+  ) -> Result<Rgb, String> {
+    let rgb = match expression {
+      ColorExpression::Color(name) if self.is_reference(name) => {
+        if stack.contains(name) {
+          stack.push(name.to_owned());
+          return Err(format!("circular color reference: {}", stack.join(" -> ")));
+        }
+
+        let value = get_value_for_selector(&self.colors[name], selector)
+          .ok_or_else(|| format!("`{}` has no value for `{}`.", name, selector))?;
+
+        stack.push(name.to_owned());
+        let rgb = self.evaluate(&ColorExpression::parse(&value)?, selector, stack)?;
+        stack.pop();
+
+        rgb
+      }
+      ColorExpression::Color(name) => {
+        let value = self.palette.get(name).unwrap_or(name);
+
+        parse_rgb(value).ok_or_else(|| {
+          format!(
+            "`{}` is not a css color, a palette name or an entry in `colors`.",
+            name
+          )
+        })?
+      }
+      ColorExpression::Mix(color, other, amount) => {
+        let (color, other) = (
+          self.evaluate(color, selector, stack)?,
+          self.evaluate(other, selector, stack)?,
+        );
+        let mix = |a: f64, b: f64| a + (b - a) * amount;
+
+        Rgb::from((
+          mix(color.red(), other.red()),
+          mix(color.green(), other.green()),
+          mix(color.blue(), other.blue()),
+          mix(color.alpha(), other.alpha()),
+        ))
+      }
+      ColorExpression::Alpha(color, amount) => {
+        let mut rgb = self.evaluate(color, selector, stack)?;
+        rgb.set_alpha(*amount);
+
+        rgb
+      }
+      ColorExpression::Lighten(color, amount) => {
+        change_lightness(&self.evaluate(color, selector, stack)?, *amount)
+      }
+      ColorExpression::Darken(color, amount) => {
+        change_lightness(&self.evaluate(color, selector, stack)?, -amount)
+      }
+    };
+
+    Ok(rgb)
+  }
+}
+
+/// A referenced entry uses the value for the same selector or theme, falling
+/// back to the `:root` value and then the first value.
+fn get_value_for_selector(variable: &CssVariable, selector: &str) -> Option<String> {
+  match variable {
+    CssVariable::Value(value) => Some(value.get_string()),
+    CssVariable::Object(object) => object
+      .selectors
+      .get(selector)
+      .or_else(|| {
+        object
+          .themes
+          .as_ref()
+          .and_then(|themes| themes.get(selector))
+      })
+      .or_else(|| object.selectors.get(ROOT_SELECTOR))
+      .or_else(|| object.selectors.values().next())
+      .map(|value| value.get_string()),
+  }
+}
+
+fn change_lightness(rgb: &Rgb, amount: f64) -> Rgb {
+  let (lightness, chroma, hue) = rgb_to_oklch(rgb);
+  let mut changed = fit_to_srgb((lightness + amount).clamp(0.0, 1.0), chroma, hue);
+  changed.set_alpha(rgb.alpha());
+
+  changed
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::config::Config;
+
+  use super::*;
+
+  fn resolve(colors: &str) -> IndexMap<String, CssVariable> {
+    let palette: IndexMap<String, String> =
+      serde_json::from_str(r##"{ "white": "#fff", "purple500": "#a855f7" }"##).unwrap();
+    let colors: IndexMap<String, CssVariable> = serde_json::from_str(colors).unwrap();
+
+    ColorResolver::new(&palette, &colors)
+      .resolve_colors()
+      .unwrap()
+  }
+
+  #[test]
+  fn parses_nested_expressions() {
+    pretty_assertions::assert_eq!(
+      ColorExpression::parse("alpha(mix(purple500, rgb(0, 0, 0), 20%), 0.5)"),
+      Ok(ColorExpression::Alpha(
+        Box::new(ColorExpression::Mix(
+          Box::new(ColorExpression::Color("purple500".into())),
+          Box::new(ColorExpression::Color("rgb(0, 0, 0)".into())),
+          0.2
+        )),
+        0.5
+      ))
+    );
+  }
+
+  #[test]
+  fn evaluates_expressions() {
+    let colors = resolve(
+      r#"{
+        "primary": "purple500",
+        "tint": "mix(primary, white, 50%)",
+        "faded": "alpha(primary, 50%)",
+        "light": "lighten(#000, 50%)",
+        "dark": "darken(white, 100%)"
+      }"#,
+    );
+    let values: Vec<_> = colors
+      .values()
+      .map(|value| get_value_for_selector(value, ROOT_SELECTOR).unwrap())
+      .collect();
+
+    pretty_assertions::assert_eq!(
+      values,
+      vec!["purple500", "#d4aafb", "#a855f780", "#636363", "#000000"]
+    );
+  }
+
+  #[test]
+  fn references_use_the_same_selector() {
+    let colors = resolve(
+      r##"{
+        "text": { "selectors": { ":root": "#000", "html.dark": "#fff" } },
+        "muted": { "selectors": { ":root": "alpha(text, 0.5)", "html.dark": "alpha(text, 0.5)" } }
+      }"##,
+    );
+
+    pretty_assertions::assert_eq!(
+      colors["muted"],
+      serde_json::from_str(
+        r##"{ "selectors": { ":root": "#00000080", "html.dark": "#ffffff80" } }"##
+      )
+      .unwrap()
+    );
+  }
+
+  #[test]
+  fn reports_circular_references() {
+    let palette = IndexMap::new();
+    let colors: IndexMap<String, CssVariable> =
+      serde_json::from_str(r#"{ "a": "darken(b, 10%)", "b": "lighten(a, 10%)" }"#).unwrap();
+    let resolver = ColorResolver::new(&palette, &colors);
+
+    pretty_assertions::assert_eq!(
+      resolver.resolve("a", ROOT_SELECTOR, "darken(b, 10%)"),
+      Err("circular color reference: a -> b -> a".into())
+    );
+  }
+
+  #[test]
+  fn used_by_atoms() {
+    let config =
+      Config::new(r#"{ "extends": "default", "colors": { "primary": "darken(purple500, 10%)" } }"#)
+        .unwrap();
+
+    insta::assert_snapshot!(
+      config.css_variables["--sk-color-bg-primary"].selectors[ROOT_SELECTOR].get_string(),
+      @"rgba(138, 49, 212, var(--sk-bg-opacity))"
+    );
+  }
+}
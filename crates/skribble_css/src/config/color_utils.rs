@@ -1,9 +1,8 @@
-use std::str::FromStr;
-
 use colorsys::{ColorAlpha, Hsl, Rgb};
 use indexmap::IndexMap;
 
-use super::user::CssValue;
+use super::css_color::CssColor;
+use super::user::{ColorFormat, CssValue};
 
 /// Convert the color to a valid css value with the opacity set to the provided
 /// css variable.
@@ -11,6 +10,7 @@ pub(crate) fn convert_css_value_to_color(
   original: &CssValue,
   palette: &IndexMap<String, String>,
   opacity: &str,
+  format: ColorFormat,
 ) -> String {
   // Create a copy of the string value to search against.
   let mut string_value = original.get_string();
@@ -19,46 +19,218 @@ pub(crate) fn convert_css_value_to_color(
     string_value = derived_value.clone();
   }
 
-  get_rgba_color_from_string(&string_value, opacity)
+  get_color_from_string(&string_value, opacity, format)
 }
 
+/// Css keywords which are valid colors but can't be converted.
+const COLOR_KEYWORDS: [&str; 7] = [
+  "transparent",
+  "currentColor",
+  "currentcolor",
+  "inherit",
+  "initial",
+  "unset",
+  "revert",
+];
+
+/// Parse any css color and convert it to `sRGB`. Colors outside of the `sRGB`
+/// gamut are reduced to fit.
+pub(crate) fn parse_rgb(value: &str) -> Option<Rgb> {
+  CssColor::parse(value).map(|color| color.to_srgb())
+}
+
+/// Check whether the value can be used as a color without looking it up in the
+/// palette.
+pub(crate) fn is_css_color(value: &str) -> bool {
+  COLOR_KEYWORDS.contains(&value) || value.starts_with("var(") || parse_rgb(value).is_some()
+}
+
+/// Write the color in the provided format with the opacity set to the css
+/// variable.
+///
+/// Wide gamut colors keep their values for the `oklch` and `colorMix` formats
+/// and fall back to the closest `sRGB` color for the other formats.
+///
 /// Will return the string unchanged if the color provided is not valid.
-pub(crate) fn get_rgba_color_from_string(value: &str, opacity: &str) -> String {
-  let mut string_value = value.to_owned();
-
-  let rgb = if let Some(stripped) = string_value.strip_prefix('#') {
-    Rgb::from_hex_str(stripped).ok()
-  } else if string_value.starts_with("rgb") {
-    Rgb::from_str(&string_value).ok()
-  } else if string_value.starts_with("hsl") {
-    if let Ok(hsl) = Hsl::from_str(&string_value) {
-      Some(Rgb::from(hsl))
+pub(crate) fn get_color_from_string(value: &str, opacity: &str, format: ColorFormat) -> String {
+  let color = match CssColor::parse(value) {
+    Some(color) => color,
+    None => return value.to_owned(),
+  };
+
+  let rgb = color.to_srgb();
+  let wrapped_opacity = wrap_css_variable(opacity);
+  let alpha = if color.alpha < 1.0 {
+    format!("calc({} * {})", round(color.alpha, 4), wrapped_opacity)
+  } else {
+    wrapped_opacity
+  };
+
+  match format {
+    ColorFormat::Rgb => format!(
+      "rgba({}, {}, {}, {})",
+      round(rgb.red(), 2),
+      round(rgb.green(), 2),
+      round(rgb.blue(), 2),
+      alpha
+    ),
+    ColorFormat::Hsl => {
+      let hsl = Hsl::from(&rgb);
+
+      format!(
+        "hsla({}, {}%, {}%, {})",
+        round(hsl.hue(), 2),
+        round(hsl.saturation(), 2),
+        round(hsl.lightness(), 2),
+        alpha
+      )
+    }
+    ColorFormat::Hex => get_hex_string(&rgb, true),
+    ColorFormat::Oklch => {
+      let (lightness, chroma, hue) = color.to_oklch();
+
+      format!(
+        "oklch({}% {} {} / {})",
+        round(lightness * 100.0, 2),
+        round(chroma, 4),
+        round(hue, 2),
+        alpha
+      )
+    }
+    ColorFormat::ColorMix if color.is_srgb() => format!(
+      "color-mix(in srgb, {} calc({} * 100%), transparent)",
+      get_hex_string(&rgb, false),
+      alpha
+    ),
+    ColorFormat::ColorMix => {
+      let (lightness, chroma, hue) = color.to_oklch();
+
+      format!(
+        "color-mix(in oklch, oklch({}% {} {}) calc({} * 100%), transparent)",
+        round(lightness * 100.0, 2),
+        round(chroma, 4),
+        round(hue, 2),
+        alpha
+      )
+    }
+  }
+}
+
+pub(crate) fn get_hex_string(rgb: &Rgb, with_alpha: bool) -> String {
+  let channel = |value: f64| format!("{:02x}", value.round().clamp(0.0, 255.0) as u8);
+  let mut hex = format!(
+    "#{}{}{}",
+    channel(rgb.red()),
+    channel(rgb.green()),
+    channel(rgb.blue())
+  );
+
+  if with_alpha && rgb.alpha() < 1.0 {
+    hex.push_str(&channel(rgb.alpha() * 255.0));
+  }
+
+  hex
+}
+
+/// Convert the color to the `OKLCH` color space, returning the lightness (`0`
+/// to `1`), chroma and hue in degrees.
+pub(crate) fn rgb_to_oklch(rgb: &Rgb) -> (f64, f64, f64) {
+  let linear = |value: f64| {
+    let value = value / 255.0;
+
+    if value <= 0.04045 {
+      value / 12.92
     } else {
-      None
+      ((value + 0.055) / 1.055).powf(2.4)
     }
+  };
+
+  let (r, g, b) = (linear(rgb.red()), linear(rgb.green()), linear(rgb.blue()));
+  let l = (0.4122214708 * r + 0.5363325363 * g + 0.0514459929 * b).cbrt();
+  let m = (0.2119034982 * r + 0.6806995451 * g + 0.1073969566 * b).cbrt();
+  let s = (0.0883024619 * r + 0.2817188376 * g + 0.6299787005 * b).cbrt();
+
+  let lightness = 0.2104542553 * l + 0.7936177850 * m - 0.0040720468 * s;
+  let a = 1.9779984951 * l - 2.4285922050 * m + 0.4505937099 * s;
+  let b = 0.0259040371 * l + 0.7827717662 * m - 0.8086757660 * s;
+
+  let chroma = (a * a + b * b).sqrt();
+  let hue = if chroma < 1e-4 {
+    0.0
   } else {
-    None
+    b.atan2(a).to_degrees().rem_euclid(360.0)
   };
 
-  let wrapped_opacity = wrap_css_variable(opacity);
+  (lightness, chroma, hue)
+}
+
+/// Convert the `OKLCH` color to the `sRGB` color space. Returns `None` when the
+/// color can't be displayed in `sRGB`.
+pub(crate) fn oklch_to_rgb(lightness: f64, chroma: f64, hue: f64) -> Option<Rgb> {
+  let (a, b) = (
+    chroma * hue.to_radians().cos(),
+    chroma * hue.to_radians().sin(),
+  );
+
+  let l = (lightness + 0.3963377774 * a + 0.2158037573 * b).powi(3);
+  let m = (lightness - 0.1055613458 * a - 0.0638541728 * b).powi(3);
+  let s = (lightness - 0.0894841775 * a - 1.2914855480 * b).powi(3);
 
-  if let Some(rgb) = rgb {
-    let alpha = if rgb.alpha() < 1.0 {
-      format!("calc({} * {})", rgb.alpha(), wrapped_opacity)
+  let channels = [
+    4.0767416621 * l - 3.3077115913 * m + 0.2309699292 * s,
+    -1.2684380046 * l + 2.6097574011 * m - 0.3413193965 * s,
+    -0.0041960863 * l - 0.7034186147 * m + 1.7076147010 * s,
+  ];
+
+  if channels
+    .iter()
+    .any(|channel| !(-1e-4..=1.0 + 1e-4).contains(channel))
+  {
+    return None;
+  }
+
+  let gamma = |value: f64| {
+    let value = value.clamp(0.0, 1.0);
+
+    if value <= 0.0031308 {
+      value * 12.92
     } else {
-      wrapped_opacity
-    };
+      1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+  };
 
-    string_value = format!(
-      "rgba({}, {}, {}, {})",
-      rgb.red(),
-      rgb.green(),
-      rgb.blue(),
-      alpha
-    );
+  Some(Rgb::from((
+    gamma(channels[0]) * 255.0,
+    gamma(channels[1]) * 255.0,
+    gamma(channels[2]) * 255.0,
+  )))
+}
+
+/// Reduce the chroma until the color can be displayed in `sRGB`.
+pub(crate) fn fit_to_srgb(lightness: f64, chroma: f64, hue: f64) -> Rgb {
+  if let Some(rgb) = oklch_to_rgb(lightness, chroma, hue) {
+    return rgb;
   }
 
-  string_value
+  let (mut low, mut high) = (0.0, chroma);
+
+  for _ in 0..20 {
+    let middle = (low + high) / 2.0;
+
+    if oklch_to_rgb(lightness, middle, hue).is_some() {
+      low = middle;
+    } else {
+      high = middle;
+    }
+  }
+
+  oklch_to_rgb(lightness, low, hue).unwrap_or_default()
+}
+
+/// Round the number to the provided decimal places.
+pub(crate) fn round(value: f64, places: i32) -> f64 {
+  let factor = 10_f64.powi(places);
+  (value * factor).round() / factor
 }
 
 /// Wrap the opacity value in `var()` if not already done in the config.
@@ -69,3 +241,46 @@ pub(crate) fn wrap_css_variable(value: &str) -> String {
     format!("var({})", value)
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn format_color(value: &str, format: ColorFormat) -> String {
+    get_color_from_string(value, "--bg-opacity", format)
+  }
+
+  #[test]
+  fn supports_every_color_format() {
+    insta::assert_snapshot!(format_color("#7c3aed", ColorFormat::Rgb), @"rgba(124, 58, 237, var(--bg-opacity))");
+    insta::assert_snapshot!(format_color("#7c3aed", ColorFormat::Hsl), @"hsla(262.12, 83.26%, 57.84%, var(--bg-opacity))");
+    insta::assert_snapshot!(format_color("#7c3aed", ColorFormat::Hex), @"#7c3aed");
+    insta::assert_snapshot!(format_color("#7c3aed", ColorFormat::Oklch), @"oklch(54.13% 0.2466 293.01 / var(--bg-opacity))");
+    insta::assert_snapshot!(format_color("#7c3aed", ColorFormat::ColorMix), @"color-mix(in srgb, #7c3aed calc(var(--bg-opacity) * 100%), transparent)");
+  }
+
+  #[test]
+  fn keeps_the_alpha_channel() {
+    insta::assert_snapshot!(format_color("rgba(0, 0, 0, 0.5)", ColorFormat::Oklch), @"oklch(0% 0 0 / calc(0.5 * var(--bg-opacity)))");
+    insta::assert_snapshot!(format_color("rgba(0, 0, 0, 0.5)", ColorFormat::Hex), @"#00000080");
+  }
+
+  #[test]
+  fn supports_css_color_4_syntax() {
+    insta::assert_snapshot!(format_color("rebeccapurple", ColorFormat::Rgb), @"rgba(102, 51, 153, var(--bg-opacity))");
+    insta::assert_snapshot!(format_color("hwb(262 23% 7%)", ColorFormat::Hex), @"#7c3bed");
+    insta::assert_snapshot!(format_color("oklch(54.13% 0.2466 293.01 / 50%)", ColorFormat::Oklch), @"oklch(54.13% 0.2466 293.01 / calc(0.5 * var(--bg-opacity)))");
+  }
+
+  #[test]
+  fn falls_back_to_srgb_for_wide_gamut_colors() {
+    insta::assert_snapshot!(format_color("color(display-p3 0 1 0)", ColorFormat::Oklch), @"oklch(84.88% 0.3685 145.64 / var(--bg-opacity))");
+    insta::assert_snapshot!(format_color("color(display-p3 0 1 0)", ColorFormat::Rgb), @"rgba(0, 246.93, 78.58, var(--bg-opacity))");
+    insta::assert_snapshot!(format_color("color(display-p3 0 1 0)", ColorFormat::ColorMix), @"color-mix(in oklch, oklch(84.88% 0.3685 145.64) calc(var(--bg-opacity) * 100%), transparent)");
+  }
+
+  #[test]
+  fn ignores_unknown_colors() {
+    insta::assert_snapshot!(format_color("currentColor", ColorFormat::Hsl), @"currentColor");
+  }
+}
@@ -0,0 +1,773 @@
+use colorsys::{ColorAlpha, Hsl, Rgb};
+
+type Matrix = [[f64; 3]; 3];
+
+const SRGB_TO_XYZ: Matrix = [
+  [0.41239079926595934, 0.357584339383878, 0.1804807884018343],
+  [0.21263900587151027, 0.715168678767756, 0.07219231536073371],
+  [0.01933081871559182, 0.11919477979462598, 0.9505321522496607],
+];
+
+const XYZ_TO_SRGB: Matrix = [
+  [3.2409699419045226, -1.537383177570094, -0.4986107602930034],
+  [-0.9692436362808796, 1.8759675015077202, 0.04155505740717559],
+  [
+    0.05563007969699366,
+    -0.20397695888897652,
+    1.0569715142428786,
+  ],
+];
+
+const DISPLAY_P3_TO_XYZ: Matrix = [
+  [0.4865709486482162, 0.26566769316909306, 0.1982172852343625],
+  [0.2289745640697488, 0.6917385218365064, 0.079286914093745],
+  [0.0, 0.04511338185890264, 1.043944368900976],
+];
+
+const A98_RGB_TO_XYZ: Matrix = [
+  [0.5766690429101305, 0.1855582379065463, 0.1882286462349947],
+  [0.29734497525053605, 0.6273635662554661, 0.07529145849399788],
+  [0.02703136138641234, 0.07068885253582723, 0.9913375368376388],
+];
+
+const PROPHOTO_RGB_TO_XYZ_D50: Matrix = [
+  [0.7977604896723027, 0.13518583717574031, 0.0313493495815248],
+  [
+    0.2880711282292934,
+    0.7118432178101014,
+    0.00008565396060525902,
+  ],
+  [0.0, 0.0, 0.8251046025104601],
+];
+
+const REC2020_TO_XYZ: Matrix = [
+  [0.6369580483012914, 0.14461690358620832, 0.1688809751641721],
+  [0.2627002120112671, 0.6779980715188708, 0.05930171646986196],
+  [0.0, 0.028072693049087428, 1.060985057710791],
+];
+
+const D50_TO_D65: Matrix = [
+  [
+    0.9554734527042182,
+    -0.023098536874261423,
+    0.0632593086610217,
+  ],
+  [
+    -0.028369706963208136,
+    1.0099954580058226,
+    0.021041398966943008,
+  ],
+  [
+    0.012314001688319899,
+    -0.020507696433477912,
+    1.3303659366080753,
+  ],
+];
+
+const XYZ_TO_LMS: Matrix = [
+  [0.819022437996703, 0.3619062600528904, -0.1288737815209879],
+  [0.0329836539323885, 0.9292868615863434, 0.0361446663506424],
+  [0.0481771893596242, 0.2642395317527308, 0.6335478284694309],
+];
+
+const LMS_TO_OKLAB: Matrix = [
+  [0.210454268309314, 0.7936177747023054, -0.0040720430116193],
+  [1.9779985324311684, -2.42859224204858, 0.450593709617411],
+  [0.0259040424655478, 0.7827717124575296, -0.8086757549230774],
+];
+
+const OKLAB_TO_LMS: Matrix = [
+  [1.0, 0.3963377773761749, 0.2158037573099136],
+  [1.0, -0.1055613458156586, -0.0638541728258133],
+  [1.0, -0.0894841775298119, -1.2914855480194092],
+];
+
+const LMS_TO_XYZ: Matrix = [
+  [1.2268798758459243, -0.5578149944602171, 0.2813910456659647],
+  [-0.0405757452148008, 1.112286803280317, -0.0717110580655164],
+  [-0.0763729366746601, -0.4214933324022432, 1.5869240198367816],
+];
+
+/// The `D50` white point used by `lab()`, `lch()` and `xyz-d50`.
+const D50_WHITE: [f64; 3] = [0.3457 / 0.3585, 1.0, (1.0 - 0.3457 - 0.3585) / 0.3585];
+
+/// The color space of a `CssColor`. `hsl()` and `hwb()` are stored as `Srgb`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum ColorSpace {
+  Srgb,
+  SrgbLinear,
+  DisplayP3,
+  A98Rgb,
+  ProphotoRgb,
+  Rec2020,
+  XyzD50,
+  XyzD65,
+  Lab,
+  Lch,
+  Oklab,
+  Oklch,
+}
+
+/// A color parsed from any css color syntax.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct CssColor {
+  pub space: ColorSpace,
+
+  /// The components in the units of the color space, e.g. `0` to `1` for the
+  /// rgb spaces and `0` to `100` for the lightness of `lab()`.
+  pub components: [f64; 3],
+  pub alpha: f64,
+}
+
+impl CssColor {
+  /// Parse hex, named colors and every color function from CSS Color 4.
+  pub(crate) fn parse(value: &str) -> Option<Self> {
+    let value = value.trim().to_lowercase();
+
+    if let Some(hex) = value.strip_prefix('#') {
+      return parse_hex(hex);
+    }
+
+    if let Some(hex) = get_named_color(&value) {
+      return parse_hex(hex);
+    }
+
+    let (name, arguments) = value.strip_suffix(')')?.split_once('(')?;
+    let arguments = Arguments::parse(arguments)?;
+
+    match name.trim() {
+      "rgb" | "rgba" => arguments.rgb(),
+      "hsl" | "hsla" => arguments.hsl(),
+      "hwb" => arguments.hwb(),
+      "lab" => arguments.with_space(ColorSpace::Lab, [100.0, 125.0, 125.0]),
+      "lch" => arguments.with_space(ColorSpace::Lch, [100.0, 150.0, 0.0]),
+      "oklab" => arguments.with_space(ColorSpace::Oklab, [1.0, 0.4, 0.4]),
+      "oklch" => arguments.with_space(ColorSpace::Oklch, [1.0, 0.4, 0.0]),
+      "color" => arguments.color(),
+      _ => None,
+    }
+  }
+
+  /// Whether the color was written in `sRGB`, either as hex, a named color,
+  /// `rgb()`, `hsl()` or `hwb()`.
+  pub(crate) fn is_srgb(&self) -> bool {
+    self.space == ColorSpace::Srgb
+  }
+
+  /// Convert to `sRGB`. Colors outside of the `sRGB` gamut have their chroma
+  /// reduced until they fit.
+  pub(crate) fn to_srgb(&self) -> Rgb {
+    let mut rgb = match self.space {
+      ColorSpace::Srgb => {
+        let [red, green, blue] = self.components;
+        Rgb::from((red * 255.0, green * 255.0, blue * 255.0))
+      }
+      _ => {
+        let linear = multiply(&XYZ_TO_SRGB, self.to_xyz());
+
+        if linear
+          .iter()
+          .all(|channel| (-1e-4..=1.0 + 1e-4).contains(channel))
+        {
+          let [red, green, blue] =
+            linear.map(|channel| srgb_gamma(channel.clamp(0.0, 1.0)) * 255.0);
+          Rgb::from((red, green, blue))
+        } else {
+          let (lightness, chroma, hue) = self.to_oklch();
+          super::color_utils::fit_to_srgb(lightness, chroma, hue)
+        }
+      }
+    };
+
+    rgb.set_alpha(self.alpha);
+    rgb
+  }
+
+  /// Convert to `OKLCH`, returning the lightness (`0` to `1`), chroma and hue
+  /// in degrees. The gamut is not reduced.
+  pub(crate) fn to_oklch(&self) -> (f64, f64, f64) {
+    let [lightness, a, b] = match self.space {
+      ColorSpace::Oklch => return (self.components[0], self.components[1], self.components[2]),
+      ColorSpace::Srgb => return super::color_utils::rgb_to_oklch(&self.to_srgb()),
+      ColorSpace::Oklab => self.components,
+      _ => {
+        let lms = multiply(&XYZ_TO_LMS, self.to_xyz()).map(f64::cbrt);
+        multiply(&LMS_TO_OKLAB, lms)
+      }
+    };
+
+    let chroma = (a * a + b * b).sqrt();
+    let hue = if chroma < 1e-4 {
+      0.0
+    } else {
+      b.atan2(a).to_degrees().rem_euclid(360.0)
+    };
+
+    (lightness, chroma, hue)
+  }
+
+  /// Convert to `XYZ` with the `D65` white point.
+  fn to_xyz(&self) -> [f64; 3] {
+    let components = self.components;
+
+    match self.space {
+      ColorSpace::Srgb => multiply(&SRGB_TO_XYZ, components.map(srgb_linear)),
+      ColorSpace::SrgbLinear => multiply(&SRGB_TO_XYZ, components),
+      ColorSpace::DisplayP3 => multiply(&DISPLAY_P3_TO_XYZ, components.map(srgb_linear)),
+      ColorSpace::A98Rgb => multiply(
+        &A98_RGB_TO_XYZ,
+        components.map(|value| value.signum() * value.abs().powf(563.0 / 256.0)),
+      ),
+      ColorSpace::ProphotoRgb => {
+        let linear = components.map(|value| {
+          if value.abs() <= 16.0 / 512.0 {
+            value / 16.0
+          } else {
+            value.signum() * value.abs().powf(1.8)
+          }
+        });
+
+        multiply(&D50_TO_D65, multiply(&PROPHOTO_RGB_TO_XYZ_D50, linear))
+      }
+      ColorSpace::Rec2020 => {
+        let (alpha, beta) = (1.09929682680944, 0.018053968510807);
+        let linear = components.map(|value| {
+          if value.abs() < beta * 4.5 {
+            value / 4.5
+          } else {
+            value.signum() * ((value.abs() + alpha - 1.0) / alpha).powf(1.0 / 0.45)
+          }
+        });
+
+        multiply(&REC2020_TO_XYZ, linear)
+      }
+      ColorSpace::XyzD50 => multiply(&D50_TO_D65, components),
+      ColorSpace::XyzD65 => components,
+      ColorSpace::Lab => multiply(&D50_TO_D65, lab_to_xyz_d50(components)),
+      ColorSpace::Lch => {
+        let [lightness, chroma, hue] = components;
+        let lab = [
+          lightness,
+          chroma * hue.to_radians().cos(),
+          chroma * hue.to_radians().sin(),
+        ];
+
+        multiply(&D50_TO_D65, lab_to_xyz_d50(lab))
+      }
+      ColorSpace::Oklab | ColorSpace::Oklch => {
+        let oklab = if self.space == ColorSpace::Oklab {
+          components
+        } else {
+          let [lightness, chroma, hue] = components;
+          [
+            lightness,
+            chroma * hue.to_radians().cos(),
+            chroma * hue.to_radians().sin(),
+          ]
+        };
+
+        let lms = multiply(&OKLAB_TO_LMS, oklab).map(|value| value.powi(3));
+        multiply(&LMS_TO_XYZ, lms)
+      }
+    }
+  }
+}
+
+/// The arguments of a css color function, supporting both the legacy comma
+/// separated syntax and the space separated syntax with an optional `/ alpha`.
+struct Arguments<'a> {
+  values: Vec<&'a str>,
+  alpha: Option<&'a str>,
+  legacy: bool,
+}
+
+impl<'a> Arguments<'a> {
+  fn parse(arguments: &'a str) -> Option<Self> {
+    if arguments.contains(',') {
+      let mut values: Vec<&str> = arguments.split(',').map(str::trim).collect();
+      let alpha = if values.len() == 4 {
+        values.pop()
+      } else {
+        None
+      };
+
+      return Some(Self {
+        values,
+        alpha,
+        legacy: true,
+      });
+    }
+
+    let (values, alpha) = match arguments.split_once('/') {
+      Some((values, alpha)) => (values, Some(alpha.trim())),
+      None => (arguments, None),
+    };
+
+    Some(Self {
+      values: values.split_whitespace().collect(),
+      alpha,
+      legacy: false,
+    })
+  }
+
+  fn alpha(&self) -> Option<f64> {
+    match self.alpha {
+      Some(alpha) => parse_number(alpha, 1.0).map(|alpha| alpha.clamp(0.0, 1.0)),
+      None => Some(1.0),
+    }
+  }
+
+  fn rgb(&self) -> Option<CssColor> {
+    let [red, green, blue] = self.numbers([255.0, 255.0, 255.0])?;
+
+    Some(CssColor {
+      space: ColorSpace::Srgb,
+      components: [red / 255.0, green / 255.0, blue / 255.0],
+      alpha: self.alpha()?,
+    })
+  }
+
+  fn hsl(&self) -> Option<CssColor> {
+    let [hue, saturation, lightness] = self.hue_numbers([100.0, 100.0])?;
+    let rgb = Rgb::from(Hsl::from((hue, saturation, lightness)));
+
+    Some(from_rgb(&rgb, self.alpha()?))
+  }
+
+  fn hwb(&self) -> Option<CssColor> {
+    if self.legacy {
+      return None;
+    }
+
+    let [hue, whiteness, blackness] = self.hue_numbers([100.0, 100.0])?;
+    let (whiteness, blackness) = (whiteness / 100.0, blackness / 100.0);
+
+    let components = if whiteness + blackness >= 1.0 {
+      let gray = whiteness / (whiteness + blackness);
+      [gray, gray, gray]
+    } else {
+      let rgb = Rgb::from(Hsl::from((hue, 100.0, 50.0)));
+      [rgb.red(), rgb.green(), rgb.blue()]
+        .map(|channel| channel / 255.0 * (1.0 - whiteness - blackness) + whiteness)
+    };
+
+    Some(CssColor {
+      space: ColorSpace::Srgb,
+      components,
+      alpha: self.alpha()?,
+    })
+  }
+
+  fn with_space(&self, space: ColorSpace, percentages: [f64; 3]) -> Option<CssColor> {
+    if self.legacy {
+      return None;
+    }
+
+    let mut components = self.numbers(percentages)?;
+
+    if matches!(space, ColorSpace::Lch | ColorSpace::Oklch) {
+      components[2] = parse_hue(self.values[2])?;
+    }
+
+    Some(CssColor {
+      space,
+      components,
+      alpha: self.alpha()?,
+    })
+  }
+
+  fn color(&self) -> Option<CssColor> {
+    if self.legacy || self.values.len() != 4 {
+      return None;
+    }
+
+    let space = match self.values[0] {
+      "srgb" => ColorSpace::Srgb,
+      "srgb-linear" => ColorSpace::SrgbLinear,
+      "display-p3" => ColorSpace::DisplayP3,
+      "a98-rgb" => ColorSpace::A98Rgb,
+      "prophoto-rgb" => ColorSpace::ProphotoRgb,
+      "rec2020" => ColorSpace::Rec2020,
+      "xyz-d50" => ColorSpace::XyzD50,
+      "xyz" | "xyz-d65" => ColorSpace::XyzD65,
+      _ => return None,
+    };
+
+    let mut components = [0.0; 3];
+
+    for (component, value) in components.iter_mut().zip(&self.values[1..]) {
+      *component = parse_number(value, 1.0)?;
+    }
+
+    Some(CssColor {
+      space,
+      components,
+      alpha: self.alpha()?,
+    })
+  }
+
+  /// Parse three numbers where `100%` is the provided value.
+  fn numbers(&self, percentages: [f64; 3]) -> Option<[f64; 3]> {
+    if self.values.len() != 3 {
+      return None;
+    }
+
+    let mut numbers = [0.0; 3];
+
+    for index in 0..3 {
+      numbers[index] = parse_number(self.values[index], percentages[index])?;
+    }
+
+    Some(numbers)
+  }
+
+  /// Parse a hue followed by two numbers where `100%` is the provided value.
+  fn hue_numbers(&self, percentages: [f64; 2]) -> Option<[f64; 3]> {
+    if self.values.len() != 3 {
+      return None;
+    }
+
+    Some([
+      parse_hue(self.values[0])?,
+      parse_number(self.values[1], percentages[0])?,
+      parse_number(self.values[2], percentages[1])?,
+    ])
+  }
+}
+
+/// Parse a number or a percentage of the provided value. `none` is `0`.
+fn parse_number(value: &str, percentage: f64) -> Option<f64> {
+  if value == "none" {
+    return Some(0.0);
+  }
+
+  match value.strip_suffix('%') {
+    Some(value) => value
+      .parse::<f64>()
+      .ok()
+      .map(|value| value / 100.0 * percentage),
+    None => value.parse::<f64>().ok(),
+  }
+}
+
+/// Parse a hue in degrees, supporting the `deg`, `rad`, `grad` and `turn`
+/// units.
+fn parse_hue(value: &str) -> Option<f64> {
+  let units = [
+    ("deg", 1.0),
+    ("grad", 0.9),
+    ("rad", 180.0 / std::f64::consts::PI),
+    ("turn", 360.0),
+  ];
+
+  for (unit, factor) in units {
+    if let Some(number) = value.strip_suffix(unit) {
+      return number.parse::<f64>().ok().map(|number| number * factor);
+    }
+  }
+
+  parse_number(value, 1.0)
+}
+
+fn parse_hex(hex: &str) -> Option<CssColor> {
+  if !matches!(hex.len(), 3 | 4 | 6 | 8) || !hex.chars().all(|char| char.is_ascii_hexdigit()) {
+    return None;
+  }
+
+  let expanded: String = if hex.len() <= 4 {
+    hex.chars().flat_map(|char| [char, char]).collect()
+  } else {
+    hex.to_owned()
+  };
+
+  let channel = |index: usize| {
+    u8::from_str_radix(&expanded[index * 2..index * 2 + 2], 16)
+      .ok()
+      .map(|value| value as f64 / 255.0)
+  };
+
+  Some(CssColor {
+    space: ColorSpace::Srgb,
+    components: [channel(0)?, channel(1)?, channel(2)?],
+    alpha: if expanded.len() == 8 {
+      channel(3)?
+    } else {
+      1.0
+    },
+  })
+}
+
+fn from_rgb(rgb: &Rgb, alpha: f64) -> CssColor {
+  CssColor {
+    space: ColorSpace::Srgb,
+    components: [rgb.red() / 255.0, rgb.green() / 255.0, rgb.blue() / 255.0],
+    alpha,
+  }
+}
+
+fn multiply(matrix: &Matrix, vector: [f64; 3]) -> [f64; 3] {
+  matrix.map(|row| row[0] * vector[0] + row[1] * vector[1] + row[2] * vector[2])
+}
+
+fn srgb_linear(value: f64) -> f64 {
+  if value.abs() <= 0.04045 {
+    value / 12.92
+  } else {
+    value.signum() * ((value.abs() + 0.055) / 1.055).powf(2.4)
+  }
+}
+
+fn srgb_gamma(value: f64) -> f64 {
+  if value.abs() <= 0.0031308 {
+    value * 12.92
+  } else {
+    value.signum() * (1.055 * value.abs().powf(1.0 / 2.4) - 0.055)
+  }
+}
+
+fn lab_to_xyz_d50([lightness, a, b]: [f64; 3]) -> [f64; 3] {
+  let (kappa, epsilon) = (24389.0 / 27.0, 216.0 / 24389.0);
+  let f1 = (lightness + 16.0) / 116.0;
+  let f0 = a / 500.0 + f1;
+  let f2 = f1 - b / 200.0;
+
+  let x = if f0.powi(3) > epsilon {
+    f0.powi(3)
+  } else {
+    (116.0 * f0 - 16.0) / kappa
+  };
+  let y = if lightness > kappa * epsilon {
+    f1.powi(3)
+  } else {
+    lightness / kappa
+  };
+  let z = if f2.powi(3) > epsilon {
+    f2.powi(3)
+  } else {
+    (116.0 * f2 - 16.0) / kappa
+  };
+
+  [x * D50_WHITE[0], y * D50_WHITE[1], z * D50_WHITE[2]]
+}
+
+/// Get the hex value of the css named color. `transparent` is left out since
+/// it's treated as a keyword.
+fn get_named_color(name: &str) -> Option<&'static str> {
+  NAMED_COLORS
+    .binary_search_by_key(&name, |(name, _)| name)
+    .ok()
+    .map(|index| NAMED_COLORS[index].1)
+}
+
+/// The css named colors, sorted by name.
+const NAMED_COLORS: [(&str, &str); 148] = [
+  ("aliceblue", "f0f8ff"),
+  ("antiquewhite", "faebd7"),
+  ("aqua", "00ffff"),
+  ("aquamarine", "7fffd4"),
+  ("azure", "f0ffff"),
+  ("beige", "f5f5dc"),
+  ("bisque", "ffe4c4"),
+  ("black", "000000"),
+  ("blanchedalmond", "ffebcd"),
+  ("blue", "0000ff"),
+  ("blueviolet", "8a2be2"),
+  ("brown", "a52a2a"),
+  ("burlywood", "deb887"),
+  ("cadetblue", "5f9ea0"),
+  ("chartreuse", "7fff00"),
+  ("chocolate", "d2691e"),
+  ("coral", "ff7f50"),
+  ("cornflowerblue", "6495ed"),
+  ("cornsilk", "fff8dc"),
+  ("crimson", "dc143c"),
+  ("cyan", "00ffff"),
+  ("darkblue", "00008b"),
+  ("darkcyan", "008b8b"),
+  ("darkgoldenrod", "b8860b"),
+  ("darkgray", "a9a9a9"),
+  ("darkgreen", "006400"),
+  ("darkgrey", "a9a9a9"),
+  ("darkkhaki", "bdb76b"),
+  ("darkmagenta", "8b008b"),
+  ("darkolivegreen", "556b2f"),
+  ("darkorange", "ff8c00"),
+  ("darkorchid", "9932cc"),
+  ("darkred", "8b0000"),
+  ("darksalmon", "e9967a"),
+  ("darkseagreen", "8fbc8f"),
+  ("darkslateblue", "483d8b"),
+  ("darkslategray", "2f4f4f"),
+  ("darkslategrey", "2f4f4f"),
+  ("darkturquoise", "00ced1"),
+  ("darkviolet", "9400d3"),
+  ("deeppink", "ff1493"),
+  ("deepskyblue", "00bfff"),
+  ("dimgray", "696969"),
+  ("dimgrey", "696969"),
+  ("dodgerblue", "1e90ff"),
+  ("firebrick", "b22222"),
+  ("floralwhite", "fffaf0"),
+  ("forestgreen", "228b22"),
+  ("fuchsia", "ff00ff"),
+  ("gainsboro", "dcdcdc"),
+  ("ghostwhite", "f8f8ff"),
+  ("gold", "ffd700"),
+  ("goldenrod", "daa520"),
+  ("gray", "808080"),
+  ("green", "008000"),
+  ("greenyellow", "adff2f"),
+  ("grey", "808080"),
+  ("honeydew", "f0fff0"),
+  ("hotpink", "ff69b4"),
+  ("indianred", "cd5c5c"),
+  ("indigo", "4b0082"),
+  ("ivory", "fffff0"),
+  ("khaki", "f0e68c"),
+  ("lavender", "e6e6fa"),
+  ("lavenderblush", "fff0f5"),
+  ("lawngreen", "7cfc00"),
+  ("lemonchiffon", "fffacd"),
+  ("lightblue", "add8e6"),
+  ("lightcoral", "f08080"),
+  ("lightcyan", "e0ffff"),
+  ("lightgoldenrodyellow", "fafad2"),
+  ("lightgray", "d3d3d3"),
+  ("lightgreen", "90ee90"),
+  ("lightgrey", "d3d3d3"),
+  ("lightpink", "ffb6c1"),
+  ("lightsalmon", "ffa07a"),
+  ("lightseagreen", "20b2aa"),
+  ("lightskyblue", "87cefa"),
+  ("lightslategray", "778899"),
+  ("lightslategrey", "778899"),
+  ("lightsteelblue", "b0c4de"),
+  ("lightyellow", "ffffe0"),
+  ("lime", "00ff00"),
+  ("limegreen", "32cd32"),
+  ("linen", "faf0e6"),
+  ("magenta", "ff00ff"),
+  ("maroon", "800000"),
+  ("mediumaquamarine", "66cdaa"),
+  ("mediumblue", "0000cd"),
+  ("mediumorchid", "ba55d3"),
+  ("mediumpurple", "9370db"),
+  ("mediumseagreen", "3cb371"),
+  ("mediumslateblue", "7b68ee"),
+  ("mediumspringgreen", "00fa9a"),
+  ("mediumturquoise", "48d1cc"),
+  ("mediumvioletred", "c71585"),
+  ("midnightblue", "191970"),
+  ("mintcream", "f5fffa"),
+  ("mistyrose", "ffe4e1"),
+  ("moccasin", "ffe4b5"),
+  ("navajowhite", "ffdead"),
+  ("navy", "000080"),
+  ("oldlace", "fdf5e6"),
+  ("olive", "808000"),
+  ("olivedrab", "6b8e23"),
+  ("orange", "ffa500"),
+  ("orangered", "ff4500"),
+  ("orchid", "da70d6"),
+  ("palegoldenrod", "eee8aa"),
+  ("palegreen", "98fb98"),
+  ("paleturquoise", "afeeee"),
+  ("palevioletred", "db7093"),
+  ("papayawhip", "ffefd5"),
+  ("peachpuff", "ffdab9"),
+  ("peru", "cd853f"),
+  ("pink", "ffc0cb"),
+  ("plum", "dda0dd"),
+  ("powderblue", "b0e0e6"),
+  ("purple", "800080"),
+  ("rebeccapurple", "663399"),
+  ("red", "ff0000"),
+  ("rosybrown", "bc8f8f"),
+  ("royalblue", "4169e1"),
+  ("saddlebrown", "8b4513"),
+  ("salmon", "fa8072"),
+  ("sandybrown", "f4a460"),
+  ("seagreen", "2e8b57"),
+  ("seashell", "fff5ee"),
+  ("sienna", "a0522d"),
+  ("silver", "c0c0c0"),
+  ("skyblue", "87ceeb"),
+  ("slateblue", "6a5acd"),
+  ("slategray", "708090"),
+  ("slategrey", "708090"),
+  ("snow", "fffafa"),
+  ("springgreen", "00ff7f"),
+  ("steelblue", "4682b4"),
+  ("tan", "d2b48c"),
+  ("teal", "008080"),
+  ("thistle", "d8bfd8"),
+  ("tomato", "ff6347"),
+  ("turquoise", "40e0d0"),
+  ("violet", "ee82ee"),
+  ("wheat", "f5deb3"),
+  ("white", "ffffff"),
+  ("whitesmoke", "f5f5f5"),
+  ("yellow", "ffff00"),
+  ("yellowgreen", "9acd32"),
+];
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn to_hex(value: &str) -> String {
+    super::super::color_utils::get_hex_string(&CssColor::parse(value).unwrap().to_srgb(), true)
+  }
+
+  #[test]
+  fn named_colors_are_sorted() {
+    assert!(NAMED_COLORS.windows(2).all(|pair| pair[0].0 < pair[1].0));
+  }
+
+  #[test]
+  fn parses_every_syntax() {
+    let colors = [
+      "#7c3aed",
+      "#7C3AED",
+      "rebeccapurple",
+      "RebeccaPurple",
+      "rgb(124 58 237)",
+      "rgb(124, 58, 237)",
+      "rgba(48.63% 22.75% 92.94% / 50%)",
+      "hsl(262.12 83.26% 57.84%)",
+      "hsl(262.12deg, 83.26%, 57.84%)",
+      "hwb(262 23% 7%)",
+      "oklch(54.13% 0.2466 293.01)",
+      "oklch(0.5413 0.2466 293.01 / 0.5)",
+      "oklab(0.5413 0.0965 -0.2269)",
+      "lab(42.23 56.98 -80.61)",
+      "lch(42.23 98.71 305.25)",
+      "color(srgb 0.4863 0.2275 0.9294)",
+      "color(display-p3 0.4543 0.2425 0.8907)",
+      "color(xyz 0.2511 0.1343 0.8139)",
+    ];
+
+    insta::assert_debug_snapshot!(colors
+      .iter()
+      .map(|color| format!("{} => {}", color, to_hex(color)))
+      .collect::<Vec<_>>());
+  }
+
+  #[test]
+  fn reduces_wide_gamut_colors() {
+    pretty_assertions::assert_eq!(to_hex("color(display-p3 0 1 0)"), "#00f74f");
+  }
+
+  #[test]
+  fn rejects_invalid_colors() {
+    for value in [
+      "#12345",
+      "notacolor",
+      "rgb(1 2)",
+      "hsl(1, 2%)",
+      "color(unknown 1 1 1)",
+    ] {
+      pretty_assertions::assert_eq!(CssColor::parse(value), None);
+    }
+  }
+}
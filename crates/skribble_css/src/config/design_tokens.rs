@@ -0,0 +1,518 @@
+use heck::{ToKebabCase, ToLowerCamelCase};
+use indexmap::IndexMap;
+use serde_json::{Map, Value};
+
+use super::{
+  error::ConfigError,
+  format::ConfigFormat,
+  user::{Atom, AtomValue, ColorPalette, CssValue, CssVariable, PaletteSource, UserConfig},
+};
+
+/// The style rule which `fontFamily` tokens are added to.
+const FONT_STYLE_RULE: &str = "font";
+
+/// The configuration created from a file of
+/// [W3C Design Tokens](https://design-tokens.github.io/community-group/format/).
+///
+/// - `color` tokens are added to the `palette` and aliases of colors are added
+///   to `colors` so that they reference the palette.
+/// - `dimension`, `duration` and `shadow` tokens are added to `variables`.
+///   Aliases use `var()` to reference the aliased variable.
+/// - `fontFamily` tokens are added as values of the `font` atom.
+///
+/// Palette and atom names are the token path in camel case, e.g.
+/// `color.blue.500` becomes `colorBlue500`. Variable names are the token path
+/// in kebab case, e.g. `--spacing-md`.
+///
+/// ```rust
+/// use skribble_css::config::DesignTokens;
+///
+/// let tokens = DesignTokens::new(
+///   r##"{ "spacing": { "$type": "dimension", "md": { "$value": "16px" } } }"##,
+/// )
+/// .unwrap();
+///
+/// assert!(tokens.variables.contains_key("--spacing-md"));
+/// assert!(tokens.skipped.is_empty());
+/// ```
+#[derive(Debug, PartialEq, Default)]
+pub struct DesignTokens {
+  pub palette: IndexMap<String, String>,
+  pub colors: IndexMap<String, CssVariable>,
+  pub variables: IndexMap<String, CssVariable>,
+  pub atoms: Vec<Atom>,
+
+  /// The tokens which couldn't be converted.
+  pub skipped: Vec<SkippedToken>,
+}
+
+/// A design token which couldn't be converted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkippedToken {
+  /// The path of the token, e.g. `color.blue.500`.
+  pub path: String,
+
+  pub reason: String,
+}
+
+/// A token collected from the document along with its inherited `$type`.
+struct Token {
+  token_type: Option<String>,
+  value: Value,
+}
+
+impl DesignTokens {
+  /// Convert the design tokens json document.
+  pub fn new(json: &str) -> Result<Self, ConfigError> {
+    let document = ConfigFormat::Json.parse(json)?;
+    let mut tokens: IndexMap<String, Token> = IndexMap::new();
+
+    match &document {
+      Value::Object(group) => collect_tokens(group, &mut vec![], None, &mut tokens),
+      _ => {
+        return Err(ConfigError::data(
+          &[],
+          "design tokens must be a json object",
+        ))
+      }
+    }
+
+    let mut design_tokens = Self::default();
+
+    for path in tokens.keys() {
+      if let Err(reason) = design_tokens.convert(&tokens, path) {
+        design_tokens.skipped.push(SkippedToken {
+          path: path.to_owned(),
+          reason,
+        });
+      }
+    }
+
+    Ok(design_tokens)
+  }
+
+  /// Add the converted tokens to the configuration. The palette is composed
+  /// with the existing palette and takes precedence over it.
+  pub fn apply_to(self, user: &mut UserConfig) {
+    if !self.palette.is_empty() {
+      let existing = std::mem::replace(&mut user.palette, ColorPalette::default());
+
+      user.palette = ColorPalette::Composite(vec![
+        PaletteSource::Palette(existing),
+        PaletteSource::Palette(ColorPalette::Object(self.palette)),
+      ]);
+    }
+
+    user.colors.extend(self.colors);
+    user.variables.extend(self.variables);
+    user.atoms.extend(self.atoms);
+  }
+
+  fn convert(&mut self, tokens: &IndexMap<String, Token>, path: &str) -> Result<(), String> {
+    let token = &tokens[path];
+    let token_type = get_token_type(tokens, path)?;
+
+    match token_type.as_str() {
+      "color" => match get_alias(&token.value) {
+        // The alias references either the palette or another entry in `colors`.
+        Some(alias) => {
+          resolve_alias_path(tokens, alias, &mut vec![path.to_owned()])?;
+
+          self.colors.insert(
+            get_name(path),
+            CssVariable::Value(CssValue::String(get_name(alias))),
+          );
+        }
+        None => {
+          self
+            .palette
+            .insert(get_name(path), get_color(&token.value)?);
+        }
+      },
+      "dimension" | "duration" => {
+        let value = match get_alias(&token.value) {
+          Some(alias) => {
+            resolve_alias_path(tokens, alias, &mut vec![path.to_owned()])?;
+            format!("var({})", get_variable_name(alias))
+          }
+          None => get_dimension(&token.value)?,
+        };
+
+        self.variables.insert(
+          get_variable_name(path),
+          CssVariable::Value(CssValue::String(value)),
+        );
+      }
+      "shadow" => {
+        let value = get_shadow(tokens, path, &token.value)?;
+
+        self.variables.insert(
+          get_variable_name(path),
+          CssVariable::Value(CssValue::String(value)),
+        );
+      }
+      "fontFamily" => {
+        let value = resolve_value(tokens, path, &token.value)?;
+        let value = match value {
+          Value::String(family) => family.to_owned(),
+          Value::Array(families) => families
+            .iter()
+            .map(|family| match family {
+              Value::String(family) if family.contains(' ') => Ok(format!("\"{}\"", family)),
+              Value::String(family) => Ok(family.to_owned()),
+              _ => Err("font families must be strings".to_owned()),
+            })
+            .collect::<Result<Vec<_>, _>>()?
+            .join(","),
+          _ => return Err("font families must be a string or a list of strings".into()),
+        };
+
+        self.add_atom_value(FONT_STYLE_RULE, get_name(path), value);
+      }
+      other => return Err(format!("`{}` tokens are not supported", other)),
+    }
+
+    Ok(())
+  }
+
+  fn add_atom_value(&mut self, style_rule: &str, name: String, value: String) {
+    let existing = self.atoms.iter_mut().find_map(|atom| match atom {
+      Atom::Value(atom) if atom.style_rules == [style_rule] => Some(atom),
+      _ => None,
+    });
+
+    match existing {
+      Some(atom) => {
+        atom.values.insert(name, CssValue::String(value));
+      }
+      None => self.atoms.push(Atom::Value(AtomValue {
+        style_rules: vec![style_rule.to_owned()],
+        values: IndexMap::from([(name, CssValue::String(value))]),
+      })),
+    }
+  }
+}
+
+/// Walk the groups collecting every token. Keys starting with `$` are
+/// properties of the group rather than child tokens.
+fn collect_tokens(
+  group: &Map<String, Value>,
+  path: &mut Vec<String>,
+  inherited_type: Option<&str>,
+  tokens: &mut IndexMap<String, Token>,
+) {
+  let group_type = group
+    .get("$type")
+    .and_then(Value::as_str)
+    .or(inherited_type);
+
+  if let Some(value) = group.get("$value") {
+    tokens.insert(
+      path.join("."),
+      Token {
+        token_type: group_type.map(ToOwned::to_owned),
+        value: value.clone(),
+      },
+    );
+
+    return;
+  }
+
+  for (key, child) in group.iter() {
+    if key.starts_with('$') {
+      continue;
+    }
+
+    if let Value::Object(child) = child {
+      path.push(key.to_owned());
+      collect_tokens(child, path, group_type, tokens);
+      path.pop();
+    }
+  }
+}
+
+/// Get the `{group.token}` path of the alias.
+fn get_alias(value: &Value) -> Option<&str> {
+  value
+    .as_str()?
+    .strip_prefix('{')?
+    .strip_suffix('}')
+    .filter(|alias| !alias.contains(['{', '}']))
+}
+
+/// Follow the alias to the token with a value, returning its path.
+fn resolve_alias_path<'a>(
+  tokens: &'a IndexMap<String, Token>,
+  alias: &'a str,
+  visited: &mut Vec<String>,
+) -> Result<&'a str, String> {
+  if visited.iter().any(|path| path == alias) {
+    visited.push(alias.to_owned());
+    return Err(format!("circular alias: {}", visited.join(" -> ")));
+  }
+
+  let (path, token) = tokens
+    .get_key_value(alias)
+    .ok_or_else(|| format!("the alias `{{{}}}` doesn't exist", alias))?;
+
+  match get_alias(&token.value) {
+    Some(next) => {
+      visited.push(alias.to_owned());
+      resolve_alias_path(tokens, next, visited)
+    }
+    None => Ok(path),
+  }
+}
+
+/// Get the value of the token, following any aliases.
+fn resolve_value<'a>(
+  tokens: &'a IndexMap<String, Token>,
+  path: &str,
+  value: &'a Value,
+) -> Result<&'a Value, String> {
+  match get_alias(value) {
+    Some(alias) => {
+      let target = resolve_alias_path(tokens, alias, &mut vec![path.to_owned()])?;
+      Ok(&tokens[target].value)
+    }
+    None => Ok(value),
+  }
+}
+
+/// Aliases without a `$type` use the type of the token they reference.
+fn get_token_type(tokens: &IndexMap<String, Token>, path: &str) -> Result<String, String> {
+  let token = &tokens[path];
+
+  match (&token.token_type, get_alias(&token.value)) {
+    (Some(token_type), _) => Ok(token_type.to_owned()),
+    (None, Some(alias)) => {
+      let target = resolve_alias_path(tokens, alias, &mut vec![path.to_owned()])?;
+
+      tokens[target]
+        .token_type
+        .clone()
+        .ok_or_else(|| "the token has no `$type`".to_owned())
+    }
+    (None, None) => Err("the token has no `$type`".into()),
+  }
+}
+
+fn get_name(path: &str) -> String {
+  path.replace('.', "-").to_lower_camel_case()
+}
+
+fn get_variable_name(path: &str) -> String {
+  format!("--{}", path.replace('.', "-").to_kebab_case())
+}
+
+/// Colors are either a css string or an object with a `colorSpace` and
+/// `components`.
+fn get_color(value: &Value) -> Result<String, String> {
+  let object = match value {
+    Value::String(color) => return Ok(color.to_owned()),
+    Value::Object(object) => object,
+    _ => return Err("colors must be a string or an object".into()),
+  };
+
+  let components = object
+    .get("components")
+    .and_then(Value::as_array)
+    .filter(|components| components.len() == 3)
+    .map(|components| {
+      components
+        .iter()
+        .map(|component| match component {
+          Value::Number(number) => number.to_string(),
+          _ => "none".to_owned(),
+        })
+        .collect::<Vec<_>>()
+    });
+  let alpha = match object.get("alpha").and_then(Value::as_f64) {
+    Some(alpha) if alpha < 1.0 => format!(" / {}", alpha),
+    _ => String::new(),
+  };
+  let color_space = object.get("colorSpace").and_then(Value::as_str);
+
+  let color = match (color_space, components) {
+    (
+      Some(
+        space @ ("srgb" | "srgb-linear" | "display-p3" | "a98-rgb" | "prophoto-rgb" | "rec2020"
+        | "xyz-d50" | "xyz-d65"),
+      ),
+      Some(components),
+    ) => Some(format!(
+      "color({} {}{})",
+      space,
+      components.join(" "),
+      alpha
+    )),
+    (Some(space @ ("oklch" | "oklab" | "lab" | "lch")), Some(components)) => {
+      Some(format!("{}({}{})", space, components.join(" "), alpha))
+    }
+    (Some(space @ ("hsl" | "hwb")), Some(components)) => Some(format!(
+      "{}({} {}% {}%{})",
+      space, components[0], components[1], components[2], alpha
+    )),
+    _ => None,
+  };
+
+  color
+    .or_else(|| {
+      object
+        .get("hex")
+        .and_then(Value::as_str)
+        .map(ToOwned::to_owned)
+    })
+    .ok_or_else(|| "the color space is not supported and there is no `hex` fallback".into())
+}
+
+/// Dimensions and durations are either a css string or an object with a
+/// `value` and `unit`.
+fn get_dimension(value: &Value) -> Result<String, String> {
+  match value {
+    Value::String(dimension) => Ok(dimension.to_owned()),
+    Value::Number(number) => Ok(number.to_string()),
+    Value::Object(object) => match (object.get("value"), object.get("unit")) {
+      (Some(Value::Number(number)), Some(Value::String(unit))) => Ok(format!("{}{}", number, unit)),
+      _ => Err("dimensions must have a numeric `value` and a `unit`".into()),
+    },
+    _ => Err("dimensions must be a string or an object".into()),
+  }
+}
+
+/// Shadows are a single shadow object or a list of them. Aliased colors use
+/// their value and aliased dimensions reference their variable.
+fn get_shadow(
+  tokens: &IndexMap<String, Token>,
+  path: &str,
+  value: &Value,
+) -> Result<String, String> {
+  let shadows = match resolve_value(tokens, path, value)? {
+    Value::Array(shadows) => shadows.iter().collect::<Vec<_>>(),
+    shadow => vec![shadow],
+  };
+
+  let mut values = vec![];
+
+  for shadow in shadows {
+    let shadow = shadow
+      .as_object()
+      .ok_or_else(|| "shadows must be objects".to_owned())?;
+    let mut parts = vec![];
+
+    if shadow.get("inset").and_then(Value::as_bool) == Some(true) {
+      parts.push("inset".to_owned());
+    }
+
+    for key in ["offsetX", "offsetY", "blur", "spread"] {
+      let part = match shadow.get(key) {
+        Some(part) => part,
+        None if key == "spread" => continue,
+        None => return Err(format!("the shadow is missing `{}`", key)),
+      };
+
+      parts.push(match get_alias(part) {
+        Some(alias) => {
+          resolve_alias_path(tokens, alias, &mut vec![path.to_owned()])?;
+          format!("var({})", get_variable_name(alias))
+        }
+        None => get_dimension(part)?,
+      });
+    }
+
+    let color = shadow
+      .get("color")
+      .ok_or_else(|| "the shadow is missing `color`".to_owned())?;
+    parts.push(get_color(resolve_value(tokens, path, color)?)?);
+
+    values.push(parts.join(" "));
+  }
+
+  Ok(values.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::{
+    config::{Config, ConfigErrorKind},
+    constants::JSON_CONFIG,
+  };
+
+  use super::*;
+
+  const TOKENS: &str = r##"{
+    "color": {
+      "$type": "color",
+      "blue": {
+        "500": { "$value": "#3b82f6" },
+        "600": { "$value": { "colorSpace": "display-p3", "components": [0.15, 0.39, 0.92], "alpha": 0.9 } }
+      },
+      "primary": { "$value": "{color.blue.500}" },
+      "link": { "$value": "{color.primary}" }
+    },
+    "spacing": {
+      "$type": "dimension",
+      "md": { "$value": { "value": 16, "unit": "px" } },
+      "gutter": { "$value": "{spacing.md}" }
+    },
+    "duration": { "fast": { "$type": "duration", "$value": "150ms" } },
+    "font": {
+      "$type": "fontFamily",
+      "body": { "$value": ["Inter Variable", "sans-serif"] }
+    },
+    "shadow": {
+      "card": {
+        "$type": "shadow",
+        "$value": { "color": "{color.blue.500}", "offsetX": "0px", "offsetY": "{spacing.md}", "blur": "4px" }
+      }
+    },
+    "easing": { "$type": "cubicBezier", "standard": { "$value": [0.2, 0, 0, 1] } },
+    "broken": { "$type": "color", "$value": "{color.missing}" }
+  }"##;
+
+  #[test]
+  fn converts_design_tokens() {
+    insta::assert_debug_snapshot!(DesignTokens::new(TOKENS).unwrap());
+  }
+
+  #[test]
+  fn rejects_documents_which_are_not_objects() {
+    let error = DesignTokens::new("[]").unwrap_err();
+
+    pretty_assertions::assert_eq!(error.kind, ConfigErrorKind::Data);
+    pretty_assertions::assert_eq!(error.path, "");
+  }
+
+  #[test]
+  fn reports_circular_aliases() {
+    let tokens = DesignTokens::new(
+      r#"{ "a": { "$type": "dimension", "$value": "{b}" }, "b": { "$value": "{a}" } }"#,
+    )
+    .unwrap();
+
+    pretty_assertions::assert_eq!(
+      tokens.skipped[0],
+      SkippedToken {
+        path: "a".into(),
+        reason: "circular alias: a -> b -> a".into()
+      }
+    );
+  }
+
+  #[test]
+  fn applied_tokens_are_valid() {
+    let mut user = UserConfig::new(JSON_CONFIG).unwrap();
+    DesignTokens::new(TOKENS).unwrap().apply_to(&mut user);
+    let config = Config::from_user(user).unwrap();
+
+    pretty_assertions::assert_eq!(config.validate(), vec![]);
+    pretty_assertions::assert_eq!(
+      config.atoms["font"]["fontBody"],
+      CssValue::String("\"Inter Variable\",sans-serif".into())
+    );
+    pretty_assertions::assert_eq!(
+      config.atoms["bg"]["colorLink"].get_string(),
+      "var(--sk-color-bg-color-link)"
+    );
+  }
+}
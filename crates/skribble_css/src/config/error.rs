@@ -0,0 +1,454 @@
+use std::{fmt, io, path::Path};
+
+use indexmap::IndexMap;
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::Value;
+use serde_path_to_error::Segment;
+
+use super::user::{
+  AtomColor, AtomFluid, AtomScale, AtomValue, ColorPalette, CssValue, GeneratedPalette,
+  NamedContainerQuery, NamespacedPalette, PaletteName, PaletteSource, PopulatedCssVariable,
+};
+
+const UNTAGGED_PREFIX: &str = "data did not match any variant of untagged enum ";
+
+/// The category of a `ConfigError`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigErrorKind {
+  /// The source could not be parsed at all.
+  Syntax,
+
+  /// The source was parsed but doesn't match the shape of the configuration.
+  Data,
+
+  /// A configuration file could not be read.
+  Io,
+}
+
+/// An error raised while loading the configuration.
+///
+/// ```text
+/// invalid config at `/atoms/3/values/sans`: data did not match any variant of untagged enum CssValue
+///   expected: `CssValue::Number` or `CssValue::Decimal` or `CssValue::String`
+///   hint: css values must be a string (`"1rem"`) or a number (`10` or `1.5`).
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigError {
+  pub kind: ConfigErrorKind,
+
+  /// A [JSON pointer](https://datatracker.ietf.org/doc/html/rfc6901) to the
+  /// value which caused the error. This is empty when the error applies to the
+  /// whole document.
+  pub path: String,
+
+  /// The message provided by the parser.
+  pub message: String,
+
+  /// The variant the parser expected at `path` when the value is one of the
+  /// untagged unions in the configuration, e.g. `Atom::Value`.
+  pub expected: Option<String>,
+
+  /// A short suggestion for fixing the error.
+  pub hint: Option<String>,
+
+  /// The line and column in the source, only available for syntax errors.
+  pub location: Option<(u32, u32)>,
+}
+
+impl ConfigError {
+  pub(crate) fn syntax(error: &impl fmt::Display, location: Option<(usize, usize)>) -> Self {
+    Self {
+      kind: ConfigErrorKind::Syntax,
+      path: String::new(),
+      message: error.to_string(),
+      expected: None,
+      hint: None,
+      location: location.map(|(line, column)| (line as u32, column as u32)),
+    }
+  }
+
+  pub(crate) fn io(path: &Path, error: &io::Error) -> Self {
+    Self {
+      kind: ConfigErrorKind::Io,
+      path: String::new(),
+      message: format!("could not read `{}`: {}", path.display(), error),
+      expected: None,
+      hint: None,
+      location: None,
+    }
+  }
+
+  pub(crate) fn unsupported_format(path: &Path) -> Self {
+    Self {
+      kind: ConfigErrorKind::Io,
+      path: String::new(),
+      message: format!(
+        "`{}` is not a supported config file. Use a `.json`, `.json5`, `.toml` or `.yaml` \
+         file.",
+        path.display()
+      ),
+      expected: None,
+      hint: None,
+      location: None,
+    }
+  }
+
+  pub(crate) fn data(path: &[String], message: &str) -> Self {
+    Self {
+      kind: ConfigErrorKind::Data,
+      path: to_json_pointer(path),
+      message: message.to_owned(),
+      expected: None,
+      hint: None,
+      location: None,
+    }
+  }
+}
+
+impl ConfigError {
+  /// Nest the error within the provided key.
+  pub(crate) fn within(mut self, key: &str) -> Self {
+    self.path = format!("{}{}", to_json_pointer(&[key.to_owned()]), self.path);
+    self
+  }
+
+  /// Mark the error as coming from a file other than the one being loaded.
+  pub(crate) fn in_file(mut self, path: &Path) -> Self {
+    self.message = format!("{} (in `{}`)", self.message, path.display());
+    self
+  }
+}
+
+impl fmt::Display for ConfigError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    if let Some((line, column)) = self.location {
+      write!(f, "invalid config at {}:{}: {}", line, column, self.message)?;
+    } else if self.path.is_empty() {
+      write!(f, "invalid config: {}", self.message)?;
+    } else {
+      write!(f, "invalid config at `{}`: {}", self.path, self.message)?;
+    }
+
+    if let Some(expected) = &self.expected {
+      write!(f, "\n  expected: {}", expected)?;
+    }
+
+    if let Some(hint) = &self.hint {
+      write!(f, "\n  hint: {}", hint)?;
+    }
+
+    Ok(())
+  }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// Deserialize the already parsed value into the provided type.
+pub(crate) fn from_value<T: DeserializeOwned>(value: &Value) -> Result<T, ConfigError> {
+  deserialize_with_path(value).map_err(|(path, error)| locate(value, path, &error))
+}
+
+type PathError = (Vec<String>, serde_json::Error);
+
+fn deserialize_with_path<T: DeserializeOwned>(value: &Value) -> Result<T, PathError> {
+  serde_path_to_error::deserialize(value).map_err(|error| {
+    let path = error
+      .path()
+      .iter()
+      .filter_map(|segment| match segment {
+        Segment::Seq { index } => Some(index.to_string()),
+        Segment::Map { key } => Some(key.to_owned()),
+        Segment::Enum { variant } => Some(variant.to_owned()),
+        Segment::Unknown => None,
+      })
+      .collect();
+
+    (path, error.into_inner())
+  })
+}
+
+fn attempt<T: DeserializeOwned>(value: &Value) -> Result<(), PathError> {
+  deserialize_with_path::<T>(value).map(|_| ())
+}
+
+type Attempt = fn(&Value) -> Result<(), PathError>;
+
+struct UntaggedEnum {
+  variants: &'static [(&'static str, Attempt)],
+  hint: &'static str,
+}
+
+/// The fields of `FontSource::Object`, which has no type of its own to retry.
+#[derive(Deserialize)]
+#[allow(dead_code)]
+struct FontSourceObject {
+  url: String,
+  #[serde(default)]
+  format: Option<String>,
+}
+
+/// The fields of `FontSource::Local`.
+#[derive(Deserialize)]
+#[allow(dead_code)]
+struct FontSourceLocal {
+  local: String,
+}
+
+/// The untagged unions used in the configuration.
+///
+/// `serde` discards the errors from each variant of an untagged enum so each
+/// variant is retried here to find out where the value actually went wrong.
+fn get_untagged_enum(name: &str) -> Option<UntaggedEnum> {
+  let untagged = match name {
+    "Atom" => UntaggedEnum {
+      variants: &[
+        ("Atom::Color", attempt::<AtomColor>),
+        ("Atom::Value", attempt::<AtomValue>),
+        ("Atom::Scale", attempt::<AtomScale>),
+        ("Atom::Fluid", attempt::<AtomFluid>),
+      ],
+      hint: "atoms need `styleRules` and either `values` (a map of names to css values), \
+             `colors` (`{ \"opacity\": \"--text-opacity\", \"palette\": true }`), `scale` \
+             (`{ \"start\": 0, \"end\": 96, \"multiplier\": 0.25, \"unit\": \"rem\" }`) or \
+             `fluid` (`{ \"values\": { \"xl\": [\"1.25rem\", \"1.5rem\"] } }`).",
+    },
+    "CssVariable" => UntaggedEnum {
+      variants: &[
+        ("CssVariable::Value", attempt::<CssValue>),
+        ("CssVariable::Object", attempt::<PopulatedCssVariable>),
+      ],
+      hint: "use a css value (`\"purple500\"`) or an object with `selectors` and optional \
+             `mediaQueries`, `breakpoints` and `themes` (a value for each theme name).",
+    },
+    "StyleRule" => UntaggedEnum {
+      variants: &[
+        ("StyleRule::WithValue", attempt::<(String, CssValue)>),
+        ("StyleRule::Name", attempt::<String>),
+      ],
+      hint: "use a css property (`\"padding\"`) or a property and value pair (`[\"display\", \
+             \"block\"]`).",
+    },
+    "ColorPalette" => UntaggedEnum {
+      variants: &[
+        ("ColorPalette::Named", attempt::<PaletteName>),
+        ("ColorPalette::Generated", attempt::<GeneratedPalette>),
+        ("ColorPalette::Composite", attempt::<Vec<PaletteSource>>),
+        (
+          "ColorPalette::Object",
+          attempt::<indexmap::IndexMap<String, String>>,
+        ),
+      ],
+      hint: "use a bundled palette (`\"tailwind\"` or `\"openColor\"`), seed colors to \
+             generate shades from (`{ \"seeds\": { \"brand\": \"#7c3aed\" } }`), a list of \
+             palettes to combine or an object which maps color names to css colors.",
+    },
+    "PaletteSource" => UntaggedEnum {
+      variants: &[
+        ("PaletteSource::Namespaced", attempt::<NamespacedPalette>),
+        ("PaletteSource::Palette", attempt::<ColorPalette>),
+      ],
+      hint: "use a palette or `{ \"namespace\": \"oc\", \"palette\": \"openColor\" }` to \
+             prefix every color name.",
+    },
+    "CssValue" => UntaggedEnum {
+      variants: &[
+        ("CssValue::Number", attempt::<u32>),
+        ("CssValue::Decimal", attempt::<f64>),
+        ("CssValue::String", attempt::<String>),
+      ],
+      hint: "css values must be a string (`\"1rem\"`) or a number (`10` or `1.5`).",
+    },
+    "MediaQueries" => UntaggedEnum {
+      variants: &[
+        (
+          "MediaQueries::Grouped",
+          attempt::<Vec<IndexMap<String, String>>>,
+        ),
+        ("MediaQueries::Flat", attempt::<IndexMap<String, String>>),
+      ],
+      hint: "use an object which maps names to media queries (`{ \"print\": \"print\" }`) or \
+             a list of such objects where each object is a group of exclusive media queries.",
+    },
+    "ContainerQuery" => UntaggedEnum {
+      variants: &[
+        ("ContainerQuery::MinWidth", attempt::<CssValue>),
+        ("ContainerQuery::Named", attempt::<NamedContainerQuery>),
+      ],
+      hint: "use a minimum width (`\"640px\"`) or `{ \"minWidth\": \"640px\", \"container\": \
+             \"sidebar\" }` to query a named container.",
+    },
+    "FontSource" => UntaggedEnum {
+      variants: &[
+        ("FontSource::Url", attempt::<String>),
+        ("FontSource::Object", attempt::<FontSourceObject>),
+        ("FontSource::Local", attempt::<FontSourceLocal>),
+      ],
+      hint: "use a url (`\"/fonts/inter.woff2\"`), `{ \"url\": \"/fonts/inter.woff2\", \
+             \"format\": \"woff2\" }` or `{ \"local\": \"Inter\" }`.",
+    },
+    "Css" => UntaggedEnum {
+      variants: &[
+        ("Css::Style", attempt::<IndexMap<String, CssValue>>),
+        (
+          "Css::Query",
+          attempt::<IndexMap<String, IndexMap<String, CssValue>>>,
+        ),
+      ],
+      hint: "use an object which maps css properties to values (`{ \"color\": \"black\" }`) \
+             or, for an at-rule, an object which maps selectors to such objects.",
+    },
+    _ => return None,
+  };
+
+  Some(untagged)
+}
+
+/// Create the error for the provided path, digging into untagged enums to find
+/// the deepest point of failure.
+fn locate(root: &Value, mut path: Vec<String>, error: &serde_json::Error) -> ConfigError {
+  let message = error.to_string();
+  let untagged = match message
+    .strip_prefix(UNTAGGED_PREFIX)
+    .and_then(get_untagged_enum)
+  {
+    Some(untagged) => untagged,
+    None => return ConfigError::data(&path, &message),
+  };
+
+  let value = root
+    .pointer(&to_json_pointer(&path))
+    .unwrap_or(&Value::Null);
+  let mut failures: Vec<(&str, PathError)> = vec![];
+
+  for (variant, attempt) in untagged.variants {
+    if let Err(failure) = attempt(value) {
+      failures.push((variant, failure));
+    }
+  }
+
+  // The variant which made it furthest into the value is most likely the one
+  // which was intended.
+  let deepest = failures
+    .iter()
+    .enumerate()
+    .filter(|(_, (_, (nested, _)))| !nested.is_empty())
+    .max_by_key(|(index, (_, (nested, _)))| (nested.len(), usize::MAX - index))
+    .map(|(index, _)| index);
+
+  if let Some(index) = deepest {
+    let (variant, (nested, nested_error)) = failures.swap_remove(index);
+    path.extend(nested);
+
+    let mut config_error = locate(root, path, &nested_error);
+    config_error.expected.get_or_insert(variant.to_owned());
+    config_error.hint.get_or_insert(untagged.hint.to_owned());
+
+    return config_error;
+  }
+
+  let mut config_error = ConfigError::data(&path, &message);
+  config_error.expected = Some(
+    untagged
+      .variants
+      .iter()
+      .map(|(variant, _)| format!("`{}`", variant))
+      .collect::<Vec<_>>()
+      .join(" or "),
+  );
+  config_error.hint = Some(untagged.hint.to_owned());
+
+  config_error
+}
+
+/// Join the segments into a JSON pointer, escaping `~` and `/` within them.
+pub(crate) fn to_json_pointer<S: AsRef<str>>(path: &[S]) -> String {
+  path
+    .iter()
+    .map(|segment| {
+      let segment = segment.as_ref();
+      format!("/{}", segment.replace('~', "~0").replace('/', "~1"))
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::config::{user::UserConfig, Config};
+
+  use super::*;
+
+  #[test]
+  fn syntax_errors_have_a_location() {
+    let error = UserConfig::new("{\n  \"atoms\": [,]\n}").unwrap_err();
+
+    pretty_assertions::assert_eq!(error.kind, ConfigErrorKind::Syntax);
+    pretty_assertions::assert_eq!(error.location.map(|(line, _)| line), Some(2));
+  }
+
+  #[test]
+  fn reports_the_path_inside_untagged_enums() {
+    let mut json: Value = serde_json::from_str(crate::constants::JSON_CONFIG).unwrap();
+    json["atoms"][0]["values"]["sans"] = Value::Bool(true);
+
+    let error = Config::new(&json.to_string()).unwrap_err();
+    insta::assert_snapshot!(error.to_string(), @r###"
+    invalid config at `/atoms/0/values/sans`: data did not match any variant of untagged enum CssValue
+      expected: `CssValue::Number` or `CssValue::Decimal` or `CssValue::String`
+      hint: css values must be a string (`"1rem"`) or a number (`10` or `1.5`).
+    "###);
+  }
+
+  #[test]
+  fn reports_the_closest_variant() {
+    let mut json: Value = serde_json::from_str(crate::constants::JSON_CONFIG).unwrap();
+    json["atoms"][1]["colors"]["palette"] = Value::String("yes".into());
+
+    let error = Config::new(&json.to_string()).unwrap_err();
+    pretty_assertions::assert_eq!(error.path, "/atoms/1/colors/palette");
+    pretty_assertions::assert_eq!(error.expected, Some("Atom::Color".into()));
+  }
+
+  #[test]
+  fn reports_untagged_enums_outside_of_atoms() {
+    let cases = [
+      r#"{ "mediaQueries": [{ "print": 1 }] }"#,
+      r#"{ "containerQueries": { "sm": { "container": "sidebar" } } }"#,
+      r#"{ "fontFaces": [{ "family": "Inter", "src": [{ "local": 1 }] }] }"#,
+      r#"{ "globalStyles": { "body": "red" } }"#,
+    ];
+
+    let errors: Vec<String> = cases
+      .iter()
+      .map(|overrides| {
+        let mut json: Value = serde_json::from_str(crate::constants::JSON_CONFIG).unwrap();
+        let overrides: Value = serde_json::from_str(overrides).unwrap();
+
+        for (key, value) in overrides.as_object().unwrap() {
+          json[key] = value.clone();
+        }
+
+        Config::new(&json.to_string()).unwrap_err().to_string()
+      })
+      .collect();
+
+    insta::assert_snapshot!(errors.join("\n"), @r###"
+    invalid config at `/mediaQueries/0/print`: invalid type: integer `1`, expected a string
+      expected: MediaQueries::Grouped
+      hint: use an object which maps names to media queries (`{ "print": "print" }`) or a list of such objects where each object is a group of exclusive media queries.
+    invalid config at `/containerQueries/sm`: data did not match any variant of untagged enum ContainerQuery
+      expected: `ContainerQuery::MinWidth` or `ContainerQuery::Named`
+      hint: use a minimum width (`"640px"`) or `{ "minWidth": "640px", "container": "sidebar" }` to query a named container.
+    invalid config at `/fontFaces/0/src/0/local`: invalid type: integer `1`, expected a string
+      expected: FontSource::Local
+      hint: use a url (`"/fonts/inter.woff2"`), `{ "url": "/fonts/inter.woff2", "format": "woff2" }` or `{ "local": "Inter" }`.
+    invalid config at `/globalStyles/body`: data did not match any variant of untagged enum Css
+      expected: `Css::Style` or `Css::Query`
+      hint: use an object which maps css properties to values (`{ "color": "black" }`) or, for an at-rule, an object which maps selectors to such objects.
+    "###);
+  }
+
+  #[test]
+  fn escapes_the_json_pointer() {
+    pretty_assertions::assert_eq!(to_json_pointer(&["a/b", "c~d"]), "/a~1b/c~0d");
+  }
+}
@@ -0,0 +1,469 @@
+use std::path::{Path, PathBuf};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::constants::JSON_CONFIG;
+
+use super::{
+  error::{from_value, ConfigError},
+  format::{read_config_file, ConfigFormat},
+};
+
+/// The name used to extend the built in configuration.
+pub const DEFAULT_EXTENDS: &str = "default";
+
+/// How a map section is combined with the configuration it extends.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, PartialEq, Clone, Copy, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum MapMerge {
+  /// Keys are added to the extended section. When a key exists in both the
+  /// values are deep merged, with objects merged key by key and every other
+  /// value taken from the extending configuration.
+  #[default]
+  Merge,
+
+  /// The section replaces the extended section entirely.
+  Replace,
+}
+
+/// How a list section is combined with the configuration it extends.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, PartialEq, Clone, Copy, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum ListMerge {
+  /// Entries are added after the extended entries.
+  #[default]
+  Append,
+
+  /// An entry is placed before the first extended entry it shares a name with
+  /// (a modifier name, a media query name or a style rule). The shared names
+  /// are removed from the extended entries and entries which are left empty
+  /// are dropped. Entries which share no names are appended.
+  Override,
+}
+
+/// The merge strategy for each section of a configuration which `extends`
+/// another.
+///
+/// Sections which aren't listed here have a fixed strategy. `options`,
+/// `parentModifiers` and `variables` are merged key by key and `palette` is
+/// replaced.
+///
+/// ```json
+/// {
+///   "extends": "default",
+///   "merge": { "breakpoints": "replace", "atoms": "override" },
+///   "breakpoints": { "tablet": "640px", "desktop": "1024px" }
+/// }
+/// ```
+#[derive(Serialize, Deserialize, JsonSchema, Debug, PartialEq, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MergeOptions {
+  pub style_rules: MapMerge,
+  pub shorthand: MapMerge,
+  pub breakpoints: MapMerge,
+  pub colors: MapMerge,
+  pub modifiers: ListMerge,
+  pub media_queries: ListMerge,
+  pub atoms: ListMerge,
+}
+
+/// Resolve the `extends` field of the configuration recursively, returning the
+/// fully merged configuration.
+///
+/// Relative paths are resolved from the `directory` of the configuration being
+/// loaded, or the current directory when it isn't known.
+pub(crate) fn resolve_extends(
+  value: Value,
+  directory: Option<&Path>,
+  visited: &mut Vec<PathBuf>,
+) -> Result<Value, ConfigError> {
+  let extends = match value.get("extends") {
+    Some(Value::String(extends)) => extends.to_owned(),
+    _ => return Ok(value),
+  };
+
+  let merge: MergeOptions = match value.get("merge") {
+    Some(merge) => from_value(merge).map_err(|error| error.within("merge"))?,
+    None => MergeOptions::default(),
+  };
+
+  let parent = if extends == DEFAULT_EXTENDS {
+    ConfigFormat::Json.parse(JSON_CONFIG)?
+  } else {
+    let path = match directory {
+      Some(directory) => directory.join(&extends),
+      None => PathBuf::from(&extends),
+    };
+
+    // Compare canonical paths so that `../` segments can't hide a cycle. A
+    // missing file is reported when it is read.
+    let path = path.canonicalize().unwrap_or(path);
+
+    if visited.contains(&path) {
+      let chain = visited
+        .iter()
+        .chain([&path])
+        .map(|path| path.display().to_string())
+        .collect::<Vec<_>>()
+        .join(" -> ");
+
+      return Err(ConfigError::data(
+        &["extends".into()],
+        &format!("circular extends: {}", chain),
+      ));
+    }
+
+    visited.push(path.clone());
+    let parent = read_config_file(&path).map_err(|error| error.in_file(&path))?;
+    let resolved = resolve_extends(parent, path.parent(), visited)?;
+    visited.pop();
+
+    resolved
+  };
+
+  Ok(merge_config(parent, value, &merge))
+}
+
+/// Merge the `child` configuration into the `parent` configuration.
+fn merge_config(parent: Value, child: Value, merge: &MergeOptions) -> Value {
+  let mut merged = match parent {
+    Value::Object(map) => map,
+    _ => Map::new(),
+  };
+
+  let child = match child {
+    Value::Object(map) => map,
+    other => return other,
+  };
+
+  for (key, value) in child {
+    let strategy = match key.as_str() {
+      "extends" => continue,
+      "styleRules" => Strategy::Map(merge.style_rules),
+      "shorthand" => Strategy::Map(merge.shorthand),
+      "breakpoints" => Strategy::Map(merge.breakpoints),
+      "colors" => Strategy::Map(merge.colors),
+      "modifiers" => Strategy::List(merge.modifiers, get_group_names, remove_group_names),
+      "mediaQueries" => Strategy::List(merge.media_queries, get_group_names, remove_group_names),
+      "atoms" => Strategy::List(merge.atoms, get_atom_names, remove_atom_rules),
+      "palette" => Strategy::Map(MapMerge::Replace),
+      _ => Strategy::Map(MapMerge::Merge),
+    };
+
+    // A single object is treated as a list with one entry, e.g. a flat map of
+    // `mediaQueries`.
+    let existing = match (merged.remove(&key), &strategy) {
+      (Some(existing), Strategy::List(..)) => Some(Value::Array(into_array(existing))),
+      (existing, _) => existing,
+    };
+
+    let merged_value = match (existing, strategy) {
+      (Some(existing), Strategy::Map(MapMerge::Merge)) => deep_merge(existing, value),
+      (Some(Value::Array(existing)), Strategy::List(ListMerge::Append, ..)) => {
+        Value::Array(existing.into_iter().chain(into_array(value)).collect())
+      }
+      (Some(Value::Array(existing)), Strategy::List(ListMerge::Override, names, remove)) => {
+        Value::Array(override_list(existing, into_array(value), names, remove))
+      }
+      _ => value,
+    };
+
+    merged.insert(key, merged_value);
+  }
+
+  Value::Object(merged)
+}
+
+type GetNames = fn(&Value) -> Vec<String>;
+type RemoveNames = fn(&mut Value, &[String]) -> bool;
+
+enum Strategy {
+  Map(MapMerge),
+  List(ListMerge, GetNames, RemoveNames),
+}
+
+fn deep_merge(parent: Value, child: Value) -> Value {
+  match (parent, child) {
+    (Value::Object(mut parent), Value::Object(child)) => {
+      for (key, value) in child {
+        let merged = match parent.remove(&key) {
+          Some(existing) => deep_merge(existing, value),
+          None => value,
+        };
+
+        parent.insert(key, merged);
+      }
+
+      Value::Object(parent)
+    }
+    (_, child) => child,
+  }
+}
+
+fn into_array(value: Value) -> Vec<Value> {
+  match value {
+    Value::Array(array) => array,
+    other => vec![other],
+  }
+}
+
+fn override_list(
+  parent: Vec<Value>,
+  child: Vec<Value>,
+  get_names: GetNames,
+  remove_names: RemoveNames,
+) -> Vec<Value> {
+  let mut entries: Vec<Option<Value>> = parent.into_iter().map(Some).collect();
+
+  for entry in child {
+    let names = get_names(&entry);
+    let mut position: Option<usize> = None;
+
+    for (index, existing) in entries.iter_mut().enumerate() {
+      let value = match existing {
+        Some(value) if get_names(value).iter().any(|name| names.contains(name)) => value,
+        _ => continue,
+      };
+
+      position.get_or_insert(index);
+
+      if remove_names(value, &names) {
+        *existing = None;
+      }
+    }
+
+    match position {
+      Some(index) => entries.insert(index, Some(entry)),
+      None => entries.push(Some(entry)),
+    }
+  }
+
+  entries.into_iter().flatten().collect()
+}
+
+/// Get the names in a group of modifiers or media queries.
+fn get_group_names(value: &Value) -> Vec<String> {
+  value
+    .as_object()
+    .map(|group| group.keys().cloned().collect())
+    .unwrap_or_default()
+}
+
+/// Remove the names from the group, returning `true` when it is empty.
+fn remove_group_names(value: &mut Value, names: &[String]) -> bool {
+  match value.as_object_mut() {
+    Some(group) => {
+      group.retain(|name, _| !names.contains(name));
+      group.is_empty()
+    }
+    None => false,
+  }
+}
+
+fn get_atom_names(value: &Value) -> Vec<String> {
+  value
+    .get("styleRules")
+    .and_then(Value::as_array)
+    .map(|rules| {
+      rules
+        .iter()
+        .filter_map(|rule| rule.as_str().map(str::to_owned))
+        .collect()
+    })
+    .unwrap_or_default()
+}
+
+/// Remove the style rules from the atom, returning `true` when none are left.
+fn remove_atom_rules(value: &mut Value, names: &[String]) -> bool {
+  match value.get_mut("styleRules").and_then(Value::as_array_mut) {
+    Some(rules) => {
+      rules.retain(|rule| !names.iter().any(|name| *rule == *name));
+      rules.is_empty()
+    }
+    None => false,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use serde_json::json;
+
+  use crate::config::{user::UserConfig, Config, ConfigErrorKind};
+
+  use super::*;
+
+  #[test]
+  fn extends_the_default_config() {
+    let user =
+      UserConfig::new(r#"{ "extends": "default", "breakpoints": { "sm": "600px" } }"#).unwrap();
+    let default = UserConfig::new(JSON_CONFIG).unwrap();
+
+    pretty_assertions::assert_eq!(user.extends, None);
+    pretty_assertions::assert_eq!(user.breakpoints.len(), default.breakpoints.len());
+    pretty_assertions::assert_eq!(user.breakpoints["sm"].get_string(), "600px");
+    pretty_assertions::assert_eq!(user.atoms, default.atoms);
+  }
+
+  #[test]
+  fn replaces_sections() {
+    let config = Config::new(
+      r#"{
+        "extends": "default",
+        "merge": { "breakpoints": "replace" },
+        "breakpoints": { "tablet": "640px" }
+      }"#,
+    )
+    .unwrap();
+
+    pretty_assertions::assert_eq!(
+      config.user.breakpoints.keys().collect::<Vec<_>>(),
+      vec!["tablet"]
+    );
+  }
+
+  #[test]
+  fn deep_merges_colors() {
+    let merged = merge_config(
+      json!({ "colors": { "text": { "selectors": { ":root": "gray900", "html.dark": "gray50" } } } }),
+      json!({ "colors": { "text": { "selectors": { "html.dark": "gray100" } } } }),
+      &MergeOptions::default(),
+    );
+
+    pretty_assertions::assert_eq!(
+      merged,
+      json!({ "colors": { "text": { "selectors": { ":root": "gray900", "html.dark": "gray100" } } } })
+    );
+  }
+
+  #[test]
+  fn overrides_lists() {
+    let merge = MergeOptions {
+      modifiers: ListMerge::Override,
+      atoms: ListMerge::Override,
+      ..Default::default()
+    };
+    let merged = merge_config(
+      json!({
+        "modifiers": [{ "hover": ["&:hover"] }, { "valid": [], "invalid": [] }],
+        "atoms": [{ "styleRules": ["p", "m"], "values": {} }, { "styleRules": ["z"], "values": {} }]
+      }),
+      json!({
+        "modifiers": [{ "invalid": ["&:invalid"] }, { "focus": ["&:focus"] }],
+        "atoms": [{ "styleRules": ["m"], "values": { "1": "1px" } }]
+      }),
+      &merge,
+    );
+
+    pretty_assertions::assert_eq!(
+      merged,
+      json!({
+        "modifiers": [
+          { "hover": ["&:hover"] },
+          { "invalid": ["&:invalid"] },
+          { "valid": [] },
+          { "focus": ["&:focus"] }
+        ],
+        "atoms": [
+          { "styleRules": ["m"], "values": { "1": "1px" } },
+          { "styleRules": ["p"], "values": {} },
+          { "styleRules": ["z"], "values": {} }
+        ]
+      })
+    );
+  }
+
+  #[test]
+  fn appends_lists() {
+    let merged = merge_config(
+      json!({ "modifiers": [{ "hover": ["&:hover"] }] }),
+      json!({ "modifiers": [{ "focus": ["&:focus"] }] }),
+      &MergeOptions::default(),
+    );
+
+    pretty_assertions::assert_eq!(
+      merged,
+      json!({ "modifiers": [{ "hover": ["&:hover"] }, { "focus": ["&:focus"] }] })
+    );
+  }
+
+  #[test]
+  fn flat_media_queries_are_a_single_group() {
+    let merged = merge_config(
+      json!({ "mediaQueries": [{ "print": "print" }] }),
+      json!({ "mediaQueries": { "hover": "(hover: hover)", "coarse": "(pointer: coarse)" } }),
+      &MergeOptions::default(),
+    );
+
+    pretty_assertions::assert_eq!(
+      merged,
+      json!({
+        "mediaQueries": [
+          { "print": "print" },
+          { "hover": "(hover: hover)", "coarse": "(pointer: coarse)" }
+        ]
+      })
+    );
+  }
+
+  /// Create an empty directory which no other test or test run shares.
+  fn create_temp_directory(name: &str) -> PathBuf {
+    let directory =
+      std::env::temp_dir().join(format!("skribble_css_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&directory);
+    std::fs::create_dir_all(&directory).unwrap();
+
+    directory
+  }
+
+  #[test]
+  fn detects_circular_extends() {
+    let directory = create_temp_directory("circular_extends");
+    std::fs::write(directory.join("a.json"), r#"{ "extends": "b.json" }"#).unwrap();
+    std::fs::write(directory.join("b.json"), r#"{ "extends": "a.json" }"#).unwrap();
+
+    let error = UserConfig::from_path(directory.join("a.json")).unwrap_err();
+    pretty_assertions::assert_eq!(error.path, "/extends");
+    assert!(error.message.starts_with("circular extends"));
+  }
+
+  #[test]
+  fn detects_circular_extends_through_relative_paths() {
+    let directory = create_temp_directory("circular_relative_extends");
+    std::fs::create_dir_all(directory.join("sub")).unwrap();
+    std::fs::write(
+      directory.join("sub/a.json"),
+      r#"{ "extends": "../sub/b.json" }"#,
+    )
+    .unwrap();
+    std::fs::write(
+      directory.join("sub/b.json"),
+      r#"{ "extends": "../sub/a.json" }"#,
+    )
+    .unwrap();
+
+    let error = UserConfig::from_path(directory.join("sub/a.json")).unwrap_err();
+    pretty_assertions::assert_eq!(error.path, "/extends");
+    assert!(error.message.starts_with("circular extends"));
+  }
+
+  #[test]
+  fn extends_files_in_other_formats() {
+    let directory = create_temp_directory("extends_formats");
+    std::fs::write(
+      directory.join("base.toml"),
+      "extends = \"default\"\n\n[breakpoints]\ntablet = \"640px\"\n",
+    )
+    .unwrap();
+    std::fs::write(
+      directory.join("skribble.config.yaml"),
+      "extends: base.toml\nbreakpoints:\n  desktop: 1024px\n",
+    )
+    .unwrap();
+
+    let user = UserConfig::from_path(directory.join("skribble.config.yaml")).unwrap();
+    pretty_assertions::assert_eq!(user.breakpoints["tablet"].get_string(), "640px");
+    pretty_assertions::assert_eq!(user.breakpoints["desktop"].get_string(), "1024px");
+  }
+}
@@ -0,0 +1,209 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use super::error::ConfigError;
+
+/// The file formats a configuration can be written in. Every format is parsed
+/// into the same json structure so the same keys are used throughout.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum ConfigFormat {
+  #[default]
+  Json,
+  Json5,
+  Toml,
+  Yaml,
+}
+
+impl ConfigFormat {
+  /// Pick the format from the extension of the file.
+  ///
+  /// ```rust
+  /// use skribble_css::config::ConfigFormat;
+  ///
+  /// assert_eq!(ConfigFormat::from_path("skribble.config.yml"), Some(ConfigFormat::Yaml));
+  /// assert_eq!(ConfigFormat::from_path("skribble.config.js"), None);
+  /// ```
+  pub fn from_path(path: impl AsRef<Path>) -> Option<Self> {
+    let extension = path.as_ref().extension()?.to_str()?.to_lowercase();
+
+    let format = match extension.as_str() {
+      "json" => Self::Json,
+      "json5" => Self::Json5,
+      "toml" => Self::Toml,
+      "yaml" | "yml" => Self::Yaml,
+      _ => return None,
+    };
+
+    Some(format)
+  }
+
+  /// Parse the source into a json value without checking its shape.
+  pub(crate) fn parse(self, source: &str) -> Result<Value, ConfigError> {
+    match self {
+      Self::Json => serde_json::from_str(source)
+        .map_err(|error| ConfigError::syntax(&error, Some((error.line(), error.column())))),
+      Self::Json5 => json5::from_str(source).map_err(|error| {
+        let json5::Error::Message { msg, location } = error;
+        ConfigError::syntax(
+          &msg,
+          location.map(|location| (location.line, location.column)),
+        )
+      }),
+      Self::Toml => toml::from_str(source).map_err(|error| {
+        let location = error
+          .line_col()
+          .map(|(line, column)| (line + 1, column + 1));
+        ConfigError::syntax(&error, location)
+      }),
+      Self::Yaml => serde_yaml::from_str(source)
+        .map(yaml_to_json)
+        .map_err(|error| {
+          let location = error
+            .location()
+            .map(|location| (location.line(), location.column()));
+          ConfigError::syntax(&error, location)
+        }),
+    }
+  }
+}
+
+/// Read the configuration file in the format given by its extension.
+pub(crate) fn read_config_file(path: &Path) -> Result<Value, ConfigError> {
+  let format =
+    ConfigFormat::from_path(path).ok_or_else(|| ConfigError::unsupported_format(path))?;
+  let source = std::fs::read_to_string(path).map_err(|error| ConfigError::io(path, &error))?;
+
+  format.parse(&source)
+}
+
+/// Yaml allows keys which aren't strings, e.g. `1: 0.25rem`. Convert them to
+/// strings since that's what they would be in json.
+fn yaml_to_json(value: serde_yaml::Value) -> Value {
+  match value {
+    serde_yaml::Value::Null => Value::Null,
+    serde_yaml::Value::Bool(value) => Value::Bool(value),
+    serde_yaml::Value::Number(number) => {
+      serde_json::to_value(&number).unwrap_or_else(|_| Value::String(number.to_string()))
+    }
+    serde_yaml::Value::String(value) => Value::String(value),
+    serde_yaml::Value::Sequence(sequence) => {
+      Value::Array(sequence.into_iter().map(yaml_to_json).collect())
+    }
+    serde_yaml::Value::Mapping(mapping) => {
+      let mut map = Map::new();
+
+      for (key, value) in mapping {
+        let key = match yaml_to_json(key) {
+          Value::String(key) => key,
+          other => other.to_string(),
+        };
+
+        map.insert(key, yaml_to_json(value));
+      }
+
+      Value::Object(map)
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use indoc::indoc;
+
+  use crate::config::{error::ConfigErrorKind, user::UserConfig};
+
+  use super::*;
+
+  fn parse(source: &str, format: ConfigFormat) -> UserConfig {
+    UserConfig::parse(source, format).unwrap()
+  }
+
+  #[test]
+  fn formats_produce_the_same_config() {
+    let json = parse(
+      r#"{ "extends": "default", "breakpoints": { "tablet": "640px" }, "atoms": [{ "styleRules": ["p"], "values": { "1": "1px" } }] }"#,
+      ConfigFormat::Json,
+    );
+
+    let json5 = parse(
+      indoc! {r#"
+        // Comments are allowed.
+        {
+          extends: 'default',
+          breakpoints: { tablet: '640px' },
+          atoms: [{ styleRules: ['p'], values: { '1': '1px' } }],
+        }
+      "#},
+      ConfigFormat::Json5,
+    );
+
+    let toml = parse(
+      indoc! {r#"
+        # Comments are allowed.
+        extends = "default"
+
+        [breakpoints]
+        tablet = "640px"
+
+        [[atoms]]
+        styleRules = ["p"]
+        values = { 1 = "1px" }
+      "#},
+      ConfigFormat::Toml,
+    );
+
+    let yaml = parse(
+      indoc! {r#"
+        # Comments are allowed.
+        extends: default
+        breakpoints:
+          tablet: 640px
+        atoms:
+          - styleRules: [p]
+            values:
+              1: 1px
+      "#},
+      ConfigFormat::Yaml,
+    );
+
+    pretty_assertions::assert_eq!(json, json5);
+    pretty_assertions::assert_eq!(json, toml);
+    pretty_assertions::assert_eq!(json, yaml);
+  }
+
+  #[test]
+  fn rejects_unknown_extensions() {
+    let error = UserConfig::from_path("skribble.config.js").unwrap_err();
+    pretty_assertions::assert_eq!(error.kind, ConfigErrorKind::Io);
+  }
+
+  #[test]
+  fn syntax_errors_have_a_location() {
+    let sources = [
+      ("{\n  atoms: [,]\n}", ConfigFormat::Json5),
+      ("extends = \"default\"\natoms = [,]", ConfigFormat::Toml),
+      ("extends: default\natoms: [,\n", ConfigFormat::Yaml),
+    ];
+
+    for (source, format) in sources {
+      let error = UserConfig::parse(source, format).unwrap_err();
+
+      pretty_assertions::assert_eq!(error.kind, ConfigErrorKind::Syntax);
+      pretty_assertions::assert_eq!(error.location.map(|(line, _)| line), Some(2));
+    }
+  }
+
+  #[test]
+  fn data_errors_have_a_path() {
+    let error = UserConfig::parse(
+      "extends: default\nbreakpoints:\n  tablet: [640px]\n",
+      ConfigFormat::Yaml,
+    )
+    .unwrap_err();
+
+    pretty_assertions::assert_eq!(error.path, "/breakpoints/tablet");
+  }
+}
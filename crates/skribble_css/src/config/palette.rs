@@ -0,0 +1,111 @@
+use indexmap::IndexMap;
+
+use super::color_utils::{fit_to_srgb, get_hex_string, parse_rgb, rgb_to_oklch};
+
+/// The shades generated for every seed color.
+const SHADES: [u16; 11] = [50, 100, 200, 300, 400, 500, 600, 700, 800, 900, 950];
+
+/// The `OKLCH` lightness of each shade.
+const SHADE_LIGHTNESS: [f64; 11] = [
+  0.977, 0.946, 0.902, 0.827, 0.714, 0.627, 0.558, 0.496, 0.438, 0.381, 0.291,
+];
+
+/// The chroma of each shade relative to the most saturated shade. Very light
+/// and very dark shades can't hold much color.
+const SHADE_CHROMA: [f64; 11] = [
+  0.05, 0.11, 0.22, 0.41, 0.7, 0.92, 1.0, 0.92, 0.76, 0.61, 0.52,
+];
+
+/// Generate the `50` to `950` shades for the seed color, e.g. `brand50` to
+/// `brand950`.
+///
+/// The shades are created in the `OKLCH` color space so that the same shade of
+/// each color has the same perceived lightness. The seed keeps its exact value
+/// at the shade closest to its lightness and the hue is shared by every shade.
+///
+/// Returns `None` when the seed isn't a valid color.
+pub(crate) fn generate_shades(name: &str, seed: &str) -> Option<IndexMap<String, String>> {
+  let rgb = parse_rgb(seed)?;
+  let (lightness, chroma, hue) = rgb_to_oklch(&rgb);
+
+  let anchor = SHADE_LIGHTNESS
+    .iter()
+    .enumerate()
+    .min_by(|(_, a), (_, b)| {
+      (*a - lightness)
+        .abs()
+        .partial_cmp(&(*b - lightness).abs())
+        .unwrap()
+    })
+    .map(|(index, _)| index)?;
+
+  let mut shades = IndexMap::new();
+
+  for (index, shade) in SHADES.iter().enumerate() {
+    let value = if index == anchor {
+      get_hex_string(&rgb, false)
+    } else {
+      let shade_chroma = chroma * SHADE_CHROMA[index] / SHADE_CHROMA[anchor];
+      get_hex_string(
+        &fit_to_srgb(SHADE_LIGHTNESS[index], shade_chroma, hue),
+        false,
+      )
+    };
+
+    shades.insert(format!("{}{}", name, shade), value);
+  }
+
+  Some(shades)
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::config::Config;
+
+  use super::*;
+
+  #[test]
+  fn generates_every_shade() {
+    let shades = generate_shades("brand", "#7c3aed").unwrap();
+
+    pretty_assertions::assert_eq!(
+      shades.keys().map(|key| key.as_str()).collect::<Vec<_>>(),
+      vec![
+        "brand50", "brand100", "brand200", "brand300", "brand400", "brand500", "brand600",
+        "brand700", "brand800", "brand900", "brand950"
+      ]
+    );
+    insta::assert_debug_snapshot!(shades.values().collect::<Vec<_>>());
+  }
+
+  #[test]
+  fn keeps_the_seed_color() {
+    let shades = generate_shades("brand", "#7c3aed").unwrap();
+    pretty_assertions::assert_eq!(shades["brand600"], "#7c3aed");
+  }
+
+  #[test]
+  fn shades_get_darker() {
+    let shades = generate_shades("sky", "rgb(14, 165, 233)").unwrap();
+    let lightness: Vec<f64> = shades
+      .values()
+      .map(|value| rgb_to_oklch(&parse_rgb(value).unwrap()).0)
+      .collect();
+
+    assert!(lightness.windows(2).all(|pair| pair[0] > pair[1]));
+  }
+
+  #[test]
+  fn generated_palette_is_used_by_atoms() {
+    let config =
+      Config::new(r##"{ "extends": "default", "palette": { "seeds": { "brand": "#7c3aed" } } }"##)
+        .unwrap();
+
+    insta::assert_snapshot!(config.atoms["bg"]["brand600"].get_string(), @"rgba(124, 58, 237, var(--sk-bg-opacity))");
+  }
+
+  #[test]
+  fn ignores_invalid_seeds() {
+    pretty_assertions::assert_eq!(generate_shades("brand", "brand"), None);
+  }
+}
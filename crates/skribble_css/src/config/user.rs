@@ -1,18 +1,45 @@
+use std::{
+  cmp::Ordering,
+  hash::{Hash, Hasher},
+  path::Path,
+};
+
+use heck::ToUpperCamelCase;
 use indexmap::IndexMap;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::constants::{PALETTE_OPEN_COLOR, PALETTE_TAILWIND, ROOT_SELECTOR};
+use crate::{
+  constants::{PALETTE_OPEN_COLOR, PALETTE_TAILWIND, ROOT_SELECTOR},
+  utils::{prefix_css_variable, prefix_css_variables_in_string},
+};
 
-use super::color_utils::convert_css_value_to_color;
+use super::{
+  color_utils::{convert_css_value_to_color, round},
+  error::{from_value, to_json_pointer, ConfigError},
+  extends::{resolve_extends, MergeOptions},
+  format::{read_config_file, ConfigFormat},
+  palette::generate_shades,
+};
 
-pub type MediaQueries = IndexMap<String, String>;
 pub type Modifiers = IndexMap<String, Vec<String>>;
+pub type Keyframes = IndexMap<String, IndexMap<String, CssValue>>;
 
-#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct UserConfig {
+  /// The configuration to build on. Use `"default"` for the built in
+  /// configuration or a path to another configuration file. This is `None`
+  /// once the configuration has been resolved.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub extends: Option<String>,
+
+  /// How each section is combined with the configuration it `extends`.
+  #[serde(default)]
+  pub merge: MergeOptions,
+
   /// General options.
-  options: Options,
+  pub options: Options,
 
   /// Set up the style rules which determine the styles that each atom name will
   /// correspond to.
@@ -30,7 +57,9 @@ pub struct UserConfig {
 
   /// Custom media queries. Each item in the vector is a media query grouping.
   ///
-  /// Each grouping is mutually exclusive when applied.
+  /// Each grouping is mutually exclusive when applied. Queries from different
+  /// groups can be used together and are combined with `and`. A single object
+  /// is treated as one group.
   ///
   /// ```json
   /// {
@@ -42,12 +71,96 @@ pub struct UserConfig {
   /// ```
   pub media_queries: MediaQueries,
 
+  /// Feature queries which wrap styles in an `@supports` rule.
+  ///
+  /// ```json
+  /// {
+  ///   "supports": { "grid": "(display: grid)", "backdrop": "(backdrop-filter: blur(1px))" }
+  /// }
+  /// ```
+  #[serde(default)]
+  pub supports: IndexMap<String, String>,
+
+  /// Named container sizes which apply styles when the nearest container, or
+  /// the named container, is at least as wide as the size.
+  ///
+  /// ```json
+  /// {
+  ///   "containerQueries": {
+  ///     "cqMd": "28rem",
+  ///     "cardWide": { "minWidth": "32rem", "container": "card" }
+  ///   }
+  /// }
+  /// ```
+  #[serde(default)]
+  pub container_queries: IndexMap<String, ContainerQuery>,
+
   /// Modifiers are used to nest styles within a selector.
   pub parent_modifiers: Modifiers,
 
   /// Modifiers are used to nest styles within a selector.
   pub modifiers: Vec<Modifiers>,
 
+  /// Animations which are written as `@keyframes` when a used class name
+  /// references them. Each step maps css properties to their values.
+  ///
+  /// ```json
+  /// {
+  ///   "keyframes": {
+  ///     "spin": { "to": { "transform": "rotate(360deg)" } }
+  ///   }
+  /// }
+  /// ```
+  #[serde(default)]
+  pub keyframes: IndexMap<String, Keyframes>,
+
+  /// The `@font-face` rules which are written when a used class name references
+  /// the font family.
+  ///
+  /// ```json
+  /// {
+  ///   "fontFaces": [
+  ///     {
+  ///       "family": "Inter",
+  ///       "src": [{ "url": "/fonts/inter.woff2", "format": "woff2" }],
+  ///       "weight": "100 900",
+  ///       "display": "swap",
+  ///       "preload": true
+  ///     }
+  ///   ]
+  /// }
+  /// ```
+  #[serde(default)]
+  pub font_faces: Vec<FontFace>,
+
+  /// Styles written before the utilities, keyed by selector or at-rule. The
+  /// variables they reference are always declared.
+  ///
+  /// ```json
+  /// {
+  ///   "globalStyles": {
+  ///     "body": { "font-family": "Inter, sans-serif", "color": "var(--color-text)" },
+  ///     "@media print": { "body": { "color": "black" } }
+  ///   }
+  /// }
+  /// ```
+  #[serde(default)]
+  pub global_styles: IndexMap<String, Css>,
+
+  /// Named themes which `colors` and `variables` can provide values for.
+  ///
+  /// ```json
+  /// {
+  ///   "themes": {
+  ///     "light": { "default": true },
+  ///     "dark": { "selector": "html.dark", "mediaQuery": "(prefers-color-scheme: dark)" },
+  ///     "darkContrast": { "parent": "dark", "mediaQuery": "(prefers-contrast: more)" }
+  ///   }
+  /// }
+  /// ```
+  #[serde(default)]
+  pub themes: IndexMap<String, Theme>,
+
   /// The colors used. They will be transformed into the format specified in the
   /// `Options` struct.
   pub colors: IndexMap<String, CssVariable>,
@@ -60,41 +173,384 @@ pub struct UserConfig {
 }
 
 impl UserConfig {
-  pub fn new(json: &str) -> serde_json::Result<Self> {
-    let config: Self = serde_json::from_str(json)?;
-    Ok(config)
+  /// Parse the json configuration. When it `extends` another configuration the
+  /// result is fully resolved with relative paths loaded from the current
+  /// directory.
+  pub fn new(json: &str) -> Result<Self, ConfigError> {
+    Self::parse(json, ConfigFormat::Json)
+  }
+
+  /// Parse the configuration written in the provided format.
+  pub fn parse(source: &str, format: ConfigFormat) -> Result<Self, ConfigError> {
+    let value = format.parse(source)?;
+    let resolved = resolve_extends(value, None, &mut vec![])?;
+
+    from_value(&resolved)
+  }
+
+  /// Load the configuration from a `.json`, `.json5`, `.toml` or `.yaml` file,
+  /// resolving `extends` relative to the file.
+  pub fn from_path(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+    let path = path.as_ref();
+    let value = read_config_file(path)?;
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let resolved = resolve_extends(value, path.parent(), &mut vec![path.clone()])?;
+
+    from_value(&resolved)
+  }
+
+  /// Get every selector, along with an optional media query, which applies the
+  /// theme. The default theme is applied to `:root` and nested themes are
+  /// scoped by the targets of their `parent`.
+  pub fn get_theme_targets(&self, name: &str) -> Vec<ThemeTarget> {
+    self.collect_theme_targets(name, &mut vec![])
+  }
+
+  fn collect_theme_targets(&self, name: &str, visited: &mut Vec<String>) -> Vec<ThemeTarget> {
+    let theme = match self.themes.get(name) {
+      Some(theme) if !visited.iter().any(|visited| visited == name) => theme,
+      _ => return vec![],
+    };
+
+    visited.push(name.to_owned());
+
+    let parents = match &theme.parent {
+      Some(parent) => self.collect_theme_targets(parent, visited),
+      None => vec![ThemeTarget {
+        selector: ROOT_SELECTOR.to_owned(),
+        media_query: None,
+      }],
+    };
+
+    let mut targets = vec![];
+
+    for parent in parents {
+      if theme.default && theme.parent.is_none() {
+        targets.push(parent.clone());
+      }
+
+      if let Some(media_query) = &theme.media_query {
+        targets.push(ThemeTarget {
+          selector: parent.selector.clone(),
+          media_query: Some(match &parent.media_query {
+            Some(parent_query) => format!("{} and {}", parent_query, media_query),
+            None => media_query.clone(),
+          }),
+        });
+      }
+
+      if let Some(selector) = &theme.selector {
+        targets.push(ThemeTarget {
+          selector: match &theme.parent {
+            Some(_) => format!("{} {}", parent.selector, selector),
+            None => selector.clone(),
+          },
+          media_query: parent.media_query.clone(),
+        });
+      }
+    }
+
+    targets
+  }
+
+  /// Add the `variablesPrefix` to every css variable which is declared or
+  /// referenced by the configuration.
+  pub(crate) fn apply_variables_prefix(&mut self) {
+    let prefix = self.options.variables_prefix.clone();
+
+    for rules in self
+      .style_rules
+      .values_mut()
+      .chain(self.shorthand.values_mut())
+    {
+      for rule in rules.iter_mut() {
+        *rule = rule.with_variables_prefix(&prefix);
+      }
+    }
+
+    self.variables = self
+      .variables
+      .iter()
+      .map(|(name, variable)| {
+        (
+          prefix_css_variable(name, &prefix),
+          variable.with_variables_prefix(&prefix),
+        )
+      })
+      .collect();
+
+    for variable in self.colors.values_mut() {
+      *variable = variable.with_variables_prefix(&prefix);
+    }
+
+    for css in self.global_styles.values_mut() {
+      *css = css.with_variables_prefix(&prefix);
+    }
+
+    for atom in self.atoms.iter_mut() {
+      match atom {
+        Atom::Color(atom) => {
+          let opacity = prefix_css_variable(&atom.colors.opacity, &prefix);
+          atom.colors.opacity = prefix_css_variables_in_string(&opacity, &prefix);
+        }
+        Atom::Value(atom) => {
+          for value in atom.values.values_mut() {
+            *value = value.with_variables_prefix(&prefix);
+          }
+        }
+        Atom::Scale(_) | Atom::Fluid(_) => {}
+      }
+    }
   }
 }
 
-#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, PartialEq)]
 #[serde(untagged)]
 pub enum Atom {
   Color(AtomColor),
   Value(AtomValue),
+  Scale(AtomScale),
+  Fluid(AtomFluid),
 }
 
 impl Atom {
-  pub fn to_atom_value(&self) -> AtomValue {
+  /// Get the values of the atom. The `breakpoints` provide the default
+  /// viewports of fluid atoms.
+  pub fn to_atom_value(&self, breakpoints: &IndexMap<String, CssValue>) -> AtomValue {
     match self {
       Atom::Color(_) => AtomValue {
         style_rules: Vec::new(),
         values: IndexMap::new(),
       },
       Atom::Value(value) => value.clone(),
+      Atom::Scale(atom) => AtomValue {
+        style_rules: atom.style_rules.clone(),
+        values: atom.scale.to_values(),
+      },
+      Atom::Fluid(atom) => AtomValue {
+        style_rules: atom.style_rules.clone(),
+        values: atom.fluid.to_values(breakpoints),
+      },
     }
   }
 }
 
-#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, PartialEq, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct AtomValue {
   pub style_rules: Vec<String>,
   pub values: IndexMap<String, CssValue>,
 }
 
+/// The values are generated from a numeric scale rather than listed one by one.
+///
+/// ```json
+/// { "styleRules": ["p", "m"], "scale": { "start": 0, "end": 96, "multiplier": 0.25, "unit": "rem" } }
+/// ```
+#[derive(Serialize, Deserialize, JsonSchema, Debug, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AtomScale {
+  pub style_rules: Vec<String>,
+  pub scale: Scale,
+}
+
+/// The most values a single `Scale` can generate.
+pub const MAX_SCALE_STEPS: usize = 1000;
+
+/// A numeric scale which generates a value for every step from `start` to
+/// `end`. The step is used as the name of the value, e.g. `$4`, with `.`
+/// written as `_` and a `_` prefix for negative steps, e.g. `$0_5` and `$_1`.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Scale {
+  pub start: f64,
+  pub end: f64,
+
+  /// The distance between each step.
+  #[serde(default = "Scale::default_step")]
+  pub step: f64,
+
+  /// The unit added to each value, e.g. `rem`.
+  #[serde(default)]
+  pub unit: String,
+
+  /// Each step is multiplied by this amount, e.g. `0.25` so that `$4` is
+  /// `1rem`. When `ratio` is set this is the value of step `0`.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub multiplier: Option<f64>,
+
+  /// Create a modular scale where each step is `ratio` times the previous step,
+  /// e.g. `1.25` for a major third.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub ratio: Option<f64>,
+}
+
+impl Scale {
+  fn default_step() -> f64 {
+    1.0
+  }
+
+  /// The number of values the scale generates. Returns `None` when the scale
+  /// never reaches `end`.
+  pub fn get_step_count(&self) -> Option<usize> {
+    if self.step <= 0.0 || self.end < self.start {
+      return None;
+    }
+
+    Some(((self.end - self.start) / self.step + 1e-9).floor() as usize + 1)
+  }
+
+  /// Generate the named values. Returns no values when the scale never reaches
+  /// `end` or has more than `MAX_SCALE_STEPS` steps.
+  pub fn to_values(&self) -> IndexMap<String, CssValue> {
+    let mut values = IndexMap::new();
+
+    let count = match self.get_step_count() {
+      Some(count) if count <= MAX_SCALE_STEPS => count,
+      _ => return values,
+    };
+
+    let multiplier = self.multiplier.unwrap_or(1.0);
+
+    for index in 0..count {
+      let step = round(self.start + index as f64 * self.step, 4);
+      let value = match self.ratio {
+        Some(ratio) => multiplier * ratio.powf(step),
+        None => multiplier * step,
+      };
+
+      values.insert(
+        get_step_name(step),
+        CssValue::String(format!("{}{}", round(value, 4), self.unit)),
+      );
+    }
+
+    values
+  }
+}
+
+/// Names can't contain `.` or `-` so that they can be used as a property in
+/// TypeScript.
+fn get_step_name(step: f64) -> String {
+  let name = step.abs().to_string().replace('.', "_");
+
+  if step < 0.0 {
+    format!("_{}", name)
+  } else {
+    name
+  }
+}
+
+/// The values scale smoothly between two viewport widths using `clamp()`.
+///
+/// ```json
+/// { "styleRules": ["fontSize"], "fluid": { "values": { "xl": ["1.25rem", "1.5rem"] } } }
+/// ```
+#[derive(Serialize, Deserialize, JsonSchema, Debug, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AtomFluid {
+  pub style_rules: Vec<String>,
+  pub fluid: Fluid,
+}
+
+/// The root font size used to convert between `px` and `rem`.
+const ROOT_FONT_SIZE: f64 = 16.0;
+
+/// Values which grow from their minimum at `minViewport` to their maximum at
+/// `maxViewport`. Lengths must be in `px` or `rem`.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Fluid {
+  /// The viewport width where the minimum values are used. Defaults to the
+  /// smallest of the `breakpoints`.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub min_viewport: Option<CssValue>,
+
+  /// The viewport width where the maximum values are used. Defaults to the
+  /// largest of the `breakpoints`.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub max_viewport: Option<CssValue>,
+
+  /// The minimum and maximum for each named value.
+  pub values: IndexMap<String, (CssValue, CssValue)>,
+}
+
+impl Fluid {
+  /// Get the minimum and maximum viewport widths in `px`.
+  pub fn get_viewports(&self, breakpoints: &IndexMap<String, CssValue>) -> Option<(f64, f64)> {
+    let widths: Vec<f64> = breakpoints
+      .values()
+      .filter_map(|width| parse_length(&width.get_string()))
+      .collect();
+
+    let min = match &self.min_viewport {
+      Some(width) => parse_length(&width.get_string()),
+      None => widths.iter().copied().reduce(f64::min),
+    }?;
+    let max = match &self.max_viewport {
+      Some(width) => parse_length(&width.get_string()),
+      None => widths.iter().copied().reduce(f64::max),
+    }?;
+
+    (max > min).then_some((min, max))
+  }
+
+  /// Generate a `clamp()` expression for every value. Values with lengths which
+  /// can't be parsed are left out.
+  pub fn to_values(&self, breakpoints: &IndexMap<String, CssValue>) -> IndexMap<String, CssValue> {
+    let mut values = IndexMap::new();
+    let (min_viewport, max_viewport) = match self.get_viewports(breakpoints) {
+      Some(viewports) => viewports,
+      None => return values,
+    };
+
+    for (name, (min, max)) in self.values.iter() {
+      let (min, max) = (min.get_string(), max.get_string());
+      let (min_length, max_length) = match (parse_length(&min), parse_length(&max)) {
+        (Some(min_length), Some(max_length)) => (min_length, max_length),
+        _ => continue,
+      };
+
+      let slope = (max_length - min_length) / (max_viewport - min_viewport);
+      let intercept = round((min_length - slope * min_viewport) / ROOT_FONT_SIZE, 4);
+      let viewport_width = round(slope * 100.0, 4);
+      let preferred = if viewport_width < 0.0 {
+        format!("{}rem - {}vw", intercept, -viewport_width)
+      } else {
+        format!("{}rem + {}vw", intercept, viewport_width)
+      };
+      let (lower, upper) = if min_length <= max_length {
+        (min, max)
+      } else {
+        (max, min)
+      };
+
+      values.insert(
+        name.to_owned(),
+        CssValue::String(format!("clamp({}, {}, {})", lower, preferred, upper)),
+      );
+    }
+
+    values
+  }
+}
+
+/// Parse a `px` or `rem` length into `px`.
+pub(crate) fn parse_length(value: &str) -> Option<f64> {
+  let value = value.trim();
+
+  if let Some(rem) = value.strip_suffix("rem") {
+    rem.parse::<f64>().ok().map(|rem| rem * ROOT_FONT_SIZE)
+  } else if let Some(px) = value.strip_suffix("px") {
+    px.parse().ok()
+  } else {
+    value.parse::<f64>().ok().filter(|number| *number == 0.0)
+  }
+}
+
 /// Rather than values being used this will make available the values defined
 /// from the colors object.
-#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct AtomColor {
   pub style_rules: Vec<String>,
@@ -102,7 +558,7 @@ pub struct AtomColor {
 }
 
 /// The options provided to an `AtomColor` configuration object.
-#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct AtomColorOptions {
   /// The CSS Variable which is used to set the color opacity.
@@ -114,14 +570,83 @@ pub struct AtomColorOptions {
 }
 
 /// Options to use in the configuration.
-#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, PartialEq)]
 #[serde(rename_all = "camelCase")]
-struct Options {
+pub struct Options {
+  /// The format used when writing colors to css.
   #[serde(default = "ColorFormat::default")]
   pub color_format: ColorFormat,
 
   #[serde(default = "default_variables_prefix")]
   pub variables_prefix: String,
+
+  /// Write a modern reset before the `globalStyles`.
+  #[serde(default)]
+  pub preflight: bool,
+
+  /// Wrap the modifiers and parent modifiers in `:where()` so that every class
+  /// name has the specificity of one class and is ordered by its score.
+  #[serde(default)]
+  pub where_selectors: bool,
+
+  /// Wrap the output in cascade layers so that it can be ordered against the
+  /// layers of the host application.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub layers: Option<Layers>,
+}
+
+/// The names of the cascade layers, in the order they are declared. Every
+/// layer is nested in the `name` layer so that renaming one never changes the
+/// order. Every class name is placed in the layer of its highest variant, e.g.
+/// `md.focus.p` is in the `breakpoints` layer.
+///
+/// ```json
+/// {
+///   "options": { "layers": { "name": "vendor", "atoms": "utilities" } }
+/// }
+/// ```
+#[derive(Serialize, Deserialize, JsonSchema, Debug, PartialEq, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Layers {
+  /// The layer which contains every other layer so that the host application
+  /// can order the output as a whole.
+  pub name: String,
+  pub variables: String,
+  pub base: String,
+  pub shorthand: String,
+  pub atoms: String,
+  pub modifiers: String,
+  pub parent_modifiers: String,
+  pub container_queries: String,
+  pub supports: String,
+  pub media_queries: String,
+  pub breakpoints: String,
+}
+
+impl Default for Layers {
+  fn default() -> Self {
+    Self {
+      name: "skribble".into(),
+      variables: "variables".into(),
+      base: "base".into(),
+      shorthand: "shorthand".into(),
+      atoms: "atoms".into(),
+      modifiers: "modifiers".into(),
+      parent_modifiers: "parent-modifiers".into(),
+      container_queries: "container-queries".into(),
+      supports: "supports".into(),
+      media_queries: "media-queries".into(),
+      breakpoints: "breakpoints".into(),
+    }
+  }
+}
+
+impl Layers {
+  /// Get the name of the layer nested within the parent layer, e.g.
+  /// `skribble.atoms`.
+  pub fn get_qualified_name(&self, layer: &str) -> String {
+    format!("{}.{}", self.name, layer)
+  }
 }
 
 fn default_variables_prefix() -> String {
@@ -129,22 +654,67 @@ fn default_variables_prefix() -> String {
 }
 
 pub trait BreakpointHelper {
-  fn to_breakpoints(&self) -> IndexMap<Option<String>, CssValue>;
+  /// Get the media query for every breakpoint, starting with `None` for the
+  /// styles which aren't wrapped in a media query.
+  ///
+  /// Each breakpoint `md` also generates `maxMd` for widths below `md` and
+  /// `mdOnly` for widths from `md` up to the next breakpoint. The order is
+  /// the order the styles are written so that `maxSm` overrides `maxMd` and
+  /// the ranges override both.
+  fn to_breakpoints(&self) -> IndexMap<Option<String>, String>;
 }
 
 impl BreakpointHelper for IndexMap<String, CssValue> {
-  fn to_breakpoints(&self) -> IndexMap<Option<String>, CssValue> {
+  fn to_breakpoints(&self) -> IndexMap<Option<String>, String> {
     let mut breakpoints = IndexMap::new();
-    breakpoints.insert(None, CssValue::String("".to_string()));
+    breakpoints.insert(None, "".to_string());
 
     for (name, value) in self.iter() {
-      breakpoints.insert(Some(name.to_string()), value.clone());
+      breakpoints.insert(
+        Some(name.to_string()),
+        format!("(min-width: {})", value.get_string()),
+      );
+    }
+
+    for (name, value) in self.iter().rev() {
+      breakpoints.insert(
+        Some(format!("max{}", name.to_upper_camel_case())),
+        format!("(max-width: {})", get_max_width(&value.get_string())),
+      );
+    }
+
+    let next_values = self.values().skip(1).map(Some).chain([None]);
+
+    for ((name, value), next_value) in self.iter().zip(next_values) {
+      let query = match next_value {
+        Some(next_value) => format!(
+          "(min-width: {}) and (max-width: {})",
+          value.get_string(),
+          get_max_width(&next_value.get_string())
+        ),
+        None => format!("(min-width: {})", value.get_string()),
+      };
+
+      breakpoints.insert(Some(format!("{}Only", name)), query);
     }
 
     breakpoints
   }
 }
 
+/// The width just below a breakpoint. The small offset avoids both the
+/// `min-width` and `max-width` queries matching at the exact breakpoint.
+fn get_max_width(value: &str) -> String {
+  let px = value
+    .strip_suffix("px")
+    .and_then(|px| px.parse::<f64>().ok());
+
+  match px {
+    Some(px) => format!("{}px", round(px - 0.02, 2)),
+    None => format!("calc({} - 0.02px)", value),
+  }
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct NamespacedAtoms {
@@ -157,23 +727,75 @@ pub struct NamespacedAtoms {
   pub margin: IndexMap<String, CssValue>,
 }
 
-type CssSelectorStyle = IndexMap<String, String>;
+type CssSelectorStyle = IndexMap<String, CssValue>;
 type CssSelectorQuery = IndexMap<String, CssSelectorStyle>;
 
-/// Options to use in the configuration.
-#[derive(Serialize, Deserialize, Debug, PartialEq)]
+/// The styles for a selector, or the selectors and their styles within an
+/// at-rule like `@media`.
+///
+/// ```json
+/// {
+///   "body": { "color": "var(--color-text)" },
+///   "@media print": { "body": { "color": "black" } }
+/// }
+/// ```
+#[derive(Serialize, Deserialize, JsonSchema, Debug, PartialEq, Clone)]
+#[serde(untagged)]
 pub enum Css {
   Style(CssSelectorStyle),
   Query(CssSelectorQuery),
 }
 
-#[derive(Serialize, Deserialize, Debug, PartialEq)]
-enum ColorFormat {
+impl Css {
+  pub fn with_variables_prefix(&self, prefix: &str) -> Self {
+    let prefix_style = |style: &CssSelectorStyle| -> CssSelectorStyle {
+      style
+        .iter()
+        .map(|(property, value)| {
+          (
+            prefix_css_variable(property, prefix),
+            value.with_variables_prefix(prefix),
+          )
+        })
+        .collect()
+    };
+
+    match self {
+      Css::Style(style) => Css::Style(prefix_style(style)),
+      Css::Query(query) => Css::Query(
+        query
+          .iter()
+          .map(|(selector, style)| (selector.to_owned(), prefix_style(style)))
+          .collect(),
+      ),
+    }
+  }
+}
+
+/// The css color syntax used for generated colors. Every format except `hex`
+/// applies the opacity variable of the atom to the color.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, PartialEq, Clone, Copy)]
+pub enum ColorFormat {
+  /// `rgba(124, 58, 237, var(--bg-opacity))`
   #[serde(rename = "rgb")]
   Rgb,
+  /// `hsla(262.12, 83.33%, 57.84%, var(--bg-opacity))`
   #[serde(rename = "hsl")]
   Hsl,
+  /// `#7c3aed`
+  ///
+  /// Hex colors can't reference a css variable so the opacity variable is
+  /// ignored.
+  #[serde(rename = "hex")]
+  Hex,
+  /// `oklch(54.13% 0.2466 293.01 / var(--bg-opacity))`
+  #[serde(rename = "oklch")]
+  Oklch,
+  /// `color-mix(in srgb, #7c3aed calc(var(--bg-opacity) * 100%), transparent)`
+  #[serde(rename = "color-mix")]
+  ColorMix,
 }
+
 impl ColorFormat {
   fn default() -> ColorFormat {
     ColorFormat::Hsl
@@ -182,10 +804,13 @@ impl ColorFormat {
 
 /// The min width can either be a string or a number. If a number it will be
 /// interpreted as a pixel measurement.
-#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Eq, Hash, PartialOrd, Ord)]
+#[derive(
+  Serialize, Deserialize, JsonSchema, Debug, PartialEq, Clone, Eq, Hash, PartialOrd, Ord,
+)]
 #[serde(untagged)]
 pub enum CssValue {
   Number(u32),
+  Decimal(Decimal),
   String(String),
 }
 
@@ -193,26 +818,114 @@ impl CssValue {
   pub fn get_string(&self) -> String {
     match self {
       CssValue::Number(value) => value.to_string(),
+      CssValue::Decimal(value) => value.0.to_string(),
       CssValue::String(value) => value.clone(),
     }
   }
+
+  pub(crate) fn with_variables_prefix(&self, prefix: &str) -> Self {
+    match self {
+      CssValue::Number(_) | CssValue::Decimal(_) => self.clone(),
+      CssValue::String(value) => CssValue::String(prefix_css_variables_in_string(value, prefix)),
+    }
+  }
+}
+
+/// A number with a fractional part, e.g. the `1.5` of `line-height: 1.5`.
+/// Values are compared by their bits so that `CssValue` can be used as a key.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy)]
+#[serde(transparent)]
+pub struct Decimal(pub f64);
+
+impl PartialEq for Decimal {
+  fn eq(&self, other: &Self) -> bool {
+    self.0.to_bits() == other.0.to_bits()
+  }
+}
+
+impl Eq for Decimal {}
+
+impl Hash for Decimal {
+  fn hash<H: Hasher>(&self, state: &mut H) {
+    self.0.to_bits().hash(state);
+  }
+}
+
+impl PartialOrd for Decimal {
+  fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+    Some(self.cmp(other))
+  }
+}
+
+impl Ord for Decimal {
+  fn cmp(&self, other: &Self) -> Ordering {
+    self.0.total_cmp(&other.0)
+  }
 }
 
 /// An enum which describes the colors to be used in the configuration.
-#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, PartialEq, Clone)]
 #[serde(untagged)]
 pub enum ColorPalette {
-  #[serde(rename = "tailwind")]
+  /// One of the bundled palettes, `"tailwind"` or `"openColor"`.
+  Named(PaletteName),
+  Generated(GeneratedPalette),
+
+  /// Combine multiple palettes. When two palettes provide the same color name
+  /// the palette which comes last wins.
+  ///
+  /// ```json
+  /// ["tailwind", { "namespace": "oc", "palette": "openColor" }]
+  /// ```
+  Composite(Vec<PaletteSource>),
+  Object(IndexMap<String, String>),
+}
+
+/// The palettes bundled with skribble.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, PartialEq, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub enum PaletteName {
   Tailwind,
-  #[serde(rename = "openColor")]
   OpenColor,
-  Object(IndexMap<String, String>),
+}
+
+/// A palette which is generated from seed colors. Each seed creates the shades
+/// `50` to `950`.
+///
+/// ```json
+/// { "seeds": { "brand": "#7c3aed" } }
+/// ```
+#[derive(Serialize, Deserialize, JsonSchema, Debug, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GeneratedPalette {
+  pub seeds: IndexMap<String, String>,
+}
+
+/// One of the palettes in a `ColorPalette::Composite`.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, PartialEq, Clone)]
+#[serde(untagged)]
+pub enum PaletteSource {
+  Namespaced(NamespacedPalette),
+  Palette(ColorPalette),
+}
+
+/// A palette with every color name prefixed by the namespace, e.g. the
+/// namespace `oc` turns `blue500` into `ocBlue500`.
+///
+/// The namespace is joined in camelCase rather than with a `.` (`oc.blue500`)
+/// since a `.` can't be used in a class name token or a design token name.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, PartialEq, Clone)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct NamespacedPalette {
+  /// The prefix for every color name, e.g. `oc` for `ocBlue500`.
+  pub namespace: String,
+  pub palette: ColorPalette,
 }
 
 impl ColorPalette {
   /// The default value to use for the color palette.
   pub fn default() -> ColorPalette {
-    ColorPalette::Tailwind
+    ColorPalette::Named(PaletteName::Tailwind)
   }
 
   /// Convert the color palette to a mapped value.
@@ -220,16 +933,231 @@ impl ColorPalette {
     let empty_palette = IndexMap::new();
 
     match self {
-      ColorPalette::Tailwind => serde_json::from_str(PALETTE_TAILWIND).unwrap_or(empty_palette),
-      ColorPalette::OpenColor => serde_json::from_str(PALETTE_OPEN_COLOR).unwrap_or(empty_palette),
+      ColorPalette::Named(PaletteName::Tailwind) => {
+        serde_json::from_str(PALETTE_TAILWIND).unwrap_or(empty_palette)
+      }
+      ColorPalette::Named(PaletteName::OpenColor) => {
+        serde_json::from_str(PALETTE_OPEN_COLOR).unwrap_or(empty_palette)
+      }
+      ColorPalette::Generated(generated) => generated
+        .seeds
+        .iter()
+        .filter_map(|(name, seed)| generate_shades(name, seed))
+        .flatten()
+        .collect(),
+      ColorPalette::Composite(sources) => {
+        let mut palette = empty_palette;
+
+        for source in sources {
+          palette.extend(source.to_map());
+        }
+
+        palette
+      }
       ColorPalette::Object(value) => value.clone(),
     }
   }
 }
 
+impl PaletteSource {
+  /// Convert the palette to a mapped value with the namespace added to every
+  /// color name.
+  pub fn to_map(&self) -> IndexMap<String, String> {
+    match self {
+      PaletteSource::Namespaced(NamespacedPalette { namespace, palette }) => palette
+        .to_map()
+        .into_iter()
+        .map(|(name, value)| {
+          (
+            format!("{}{}", namespace, name.to_upper_camel_case()),
+            value,
+          )
+        })
+        .collect(),
+      PaletteSource::Palette(palette) => palette.to_map(),
+    }
+  }
+}
+
+/// A theme which `colors` and `variables` can provide values for.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, PartialEq, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Theme {
+  /// The selector which applies the theme, e.g. `html.dark`.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub selector: Option<String>,
+
+  /// The media query which applies the theme, e.g.
+  /// `(prefers-color-scheme: dark)`.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub media_query: Option<String>,
+
+  /// The default theme is applied to `:root`.
+  #[serde(default)]
+  pub default: bool,
+
+  /// Nest the theme within another theme so that it only applies when the
+  /// parent theme does, e.g. a high contrast version of the dark theme.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub parent: Option<String>,
+}
+
+/// Where the values of a theme are declared.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ThemeTarget {
+  pub selector: String,
+  pub media_query: Option<String>,
+}
+
+/// A font which is written as an `@font-face` rule.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FontFace {
+  /// The `font-family` name which class names use to reference the font.
+  pub family: String,
+
+  /// The sources in the order the browser should try them.
+  pub src: Vec<FontSource>,
+
+  /// The weight or a range of weights, e.g. `400` or `100 900`.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub weight: Option<CssValue>,
+
+  /// The style or a range of oblique angles, e.g. `italic`.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub style: Option<String>,
+
+  /// The `font-display` strategy, e.g. `swap`.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub display: Option<String>,
+
+  /// The `unicode-range` of characters the font provides.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub unicode_range: Option<String>,
+
+  /// Include the first `url` source in the preload links.
+  #[serde(default)]
+  pub preload: bool,
+}
+
+/// A source of a font face, either a url or an object with the format or the
+/// name of a locally installed font.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, PartialEq, Clone)]
+#[serde(untagged)]
+pub enum FontSource {
+  Url(String),
+  Object {
+    url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    format: Option<String>,
+  },
+  Local {
+    local: String,
+  },
+}
+
+/// The media queries, either as a list of groups or a single group.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, PartialEq, Clone)]
+#[serde(untagged)]
+pub enum MediaQueries {
+  Grouped(Vec<IndexMap<String, String>>),
+  Flat(IndexMap<String, String>),
+}
+
+impl MediaQueries {
+  /// Get every group of mutually exclusive media queries.
+  pub fn groups(&self) -> Vec<&IndexMap<String, String>> {
+    match self {
+      MediaQueries::Grouped(groups) => groups.iter().collect(),
+      MediaQueries::Flat(group) => vec![group],
+    }
+  }
+
+  /// Iterate over the media queries of every group in order.
+  pub fn iter(&self) -> impl Iterator<Item = (&String, &String)> {
+    self.groups().into_iter().flat_map(|group| group.iter())
+  }
+
+  pub fn keys(&self) -> impl Iterator<Item = &String> {
+    self.iter().map(|(name, _)| name)
+  }
+
+  pub fn get(&self, name: &str) -> Option<&String> {
+    self.groups().into_iter().find_map(|group| group.get(name))
+  }
+
+  /// Get the index of the group which contains the media query.
+  pub fn get_group_of(&self, name: &str) -> Option<usize> {
+    self
+      .groups()
+      .iter()
+      .position(|group| group.contains_key(name))
+  }
+
+  /// Get the position of the media query across all the groups.
+  pub fn get_index_of(&self, name: &str) -> Option<usize> {
+    self.keys().position(|key| key == name)
+  }
+
+  /// Get the combined query for media queries from different groups.
+  pub fn get_query(&self, names: &[String]) -> String {
+    names
+      .iter()
+      .filter_map(|name| self.get(name))
+      .map(|query| query.as_str())
+      .collect::<Vec<_>>()
+      .join(" and ")
+  }
+
+  /// Get the path of the media query in the configuration.
+  pub fn get_path(&self, name: &str) -> String {
+    match (self, self.get_group_of(name)) {
+      (MediaQueries::Grouped(_), Some(group)) => {
+        to_json_pointer(&["mediaQueries", &group.to_string(), name])
+      }
+      _ => to_json_pointer(&["mediaQueries", name]),
+    }
+  }
+}
+
+/// A container size which is either a minimum width or an object which also
+/// names the container to query.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, PartialEq, Clone)]
+#[serde(untagged)]
+pub enum ContainerQuery {
+  MinWidth(CssValue),
+  Named(NamedContainerQuery),
+}
+
+impl ContainerQuery {
+  /// Get the condition used in the `@container` rule.
+  pub fn get_query(&self) -> String {
+    match self {
+      ContainerQuery::MinWidth(min_width) => format!("(min-width: {})", min_width.get_string()),
+      ContainerQuery::Named(NamedContainerQuery {
+        min_width,
+        container,
+      }) => match container {
+        Some(container) => format!("{} (min-width: {})", container, min_width.get_string()),
+        None => format!("(min-width: {})", min_width.get_string()),
+      },
+    }
+  }
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct NamedContainerQuery {
+  pub min_width: CssValue,
+
+  /// The `container-name` to query instead of the nearest container.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub container: Option<String>,
+}
+
 /// Colors and variables can be defined to be different under different
 /// selectors. The default value provided
-#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, PartialEq, Clone)]
 #[serde(untagged)]
 #[allow(clippy::large_enum_variant)]
 pub enum CssVariable {
@@ -243,6 +1171,7 @@ impl CssVariable {
       selectors: IndexMap::new(),
       breakpoints: None,
       media_queries: None,
+      themes: None,
     };
 
     match self {
@@ -270,31 +1199,43 @@ impl CssVariable {
     &self,
     palette: &IndexMap<String, String>,
     opacity: &str,
+    format: ColorFormat,
   ) -> PopulatedCssVariable {
     let mut populated = PopulatedCssVariable {
       selectors: IndexMap::new(),
       breakpoints: None,
       media_queries: None,
+      themes: None,
     };
     match self {
       CssVariable::Value(original_value) => {
         populated.selectors.insert(
           ROOT_SELECTOR.to_owned(),
-          CssValue::String(convert_css_value_to_color(original_value, palette, opacity)),
+          CssValue::String(convert_css_value_to_color(
+            original_value,
+            palette,
+            opacity,
+            format,
+          )),
         );
 
         populated
       }
       CssVariable::Object(value) => {
-        populated.selectors = self.clone_with_color(palette, opacity, &value.selectors);
+        populated.selectors = self.clone_with_color(palette, opacity, format, &value.selectors);
 
         if let Some(breakpoints) = &value.breakpoints {
-          populated.breakpoints = Some(self.clone_parent_with_color(palette, opacity, breakpoints));
+          populated.breakpoints =
+            Some(self.clone_parent_with_color(palette, opacity, format, breakpoints));
         }
 
         if let Some(media_queries) = &value.media_queries {
           populated.media_queries =
-            Some(self.clone_parent_with_color(palette, opacity, media_queries));
+            Some(self.clone_parent_with_color(palette, opacity, format, media_queries));
+        }
+
+        if let Some(themes) = &value.themes {
+          populated.themes = Some(self.clone_with_color(palette, opacity, format, themes));
         }
 
         populated
@@ -302,6 +1243,13 @@ impl CssVariable {
     }
   }
 
+  pub(crate) fn with_variables_prefix(&self, prefix: &str) -> Self {
+    match self {
+      CssVariable::Value(value) => CssVariable::Value(value.with_variables_prefix(prefix)),
+      CssVariable::Object(value) => CssVariable::Object(value.with_variables_prefix(prefix)),
+    }
+  }
+
   /// Create a container css variable which has values for each breakpoint
   /// corresponding to the min width.
   fn container(&self, populated: &mut PopulatedCssVariable, user: &UserConfig) {
@@ -324,6 +1272,7 @@ impl CssVariable {
     &self,
     palette: &IndexMap<String, String>,
     opacity: &str,
+    format: ColorFormat,
     original_selectors: &CssVariableSelectors,
   ) -> CssVariableSelectors {
     let mut variable_selector = IndexMap::new();
@@ -331,7 +1280,12 @@ impl CssVariable {
     for (selector_name, selector_value) in original_selectors.iter() {
       variable_selector.insert(
         selector_name.clone(),
-        CssValue::String(convert_css_value_to_color(selector_value, palette, opacity)),
+        CssValue::String(convert_css_value_to_color(
+          selector_value,
+          palette,
+          opacity,
+          format,
+        )),
       );
     }
 
@@ -342,6 +1296,7 @@ impl CssVariable {
     &self,
     palette: &IndexMap<String, String>,
     opacity: &str,
+    format: ColorFormat,
     original: &IndexMap<String, CssVariableSelectors>,
   ) -> IndexMap<String, CssVariableSelectors> {
     let mut container = IndexMap::new();
@@ -349,7 +1304,7 @@ impl CssVariable {
     for (name, child) in original.iter() {
       container.insert(
         name.to_owned(),
-        self.clone_with_color(palette, opacity, child),
+        self.clone_with_color(palette, opacity, format, child),
       );
     }
 
@@ -360,17 +1315,45 @@ impl CssVariable {
 pub type CssVariableSelectors = IndexMap<String, CssValue>;
 pub type NestedCssVariableSelectors = IndexMap<String, CssVariableSelectors>;
 
-#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, PartialEq, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct PopulatedCssVariable {
+  #[serde(default)]
   pub selectors: CssVariableSelectors,
   pub media_queries: Option<NestedCssVariableSelectors>,
   pub breakpoints: Option<NestedCssVariableSelectors>,
+
+  /// The value for each theme defined in `themes`.
+  pub themes: Option<CssVariableSelectors>,
 }
 
-impl PopulatedCssVariable {}
+impl PopulatedCssVariable {
+  pub(crate) fn with_variables_prefix(&self, prefix: &str) -> Self {
+    let prefix_selectors = |selectors: &CssVariableSelectors| -> CssVariableSelectors {
+      selectors
+        .iter()
+        .map(|(selector, value)| (selector.clone(), value.with_variables_prefix(prefix)))
+        .collect()
+    };
+    let prefix_nested = |nested: &NestedCssVariableSelectors| -> NestedCssVariableSelectors {
+      nested
+        .iter()
+        .map(|(name, selectors)| (name.clone(), prefix_selectors(selectors)))
+        .collect()
+    };
 
-#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Eq, Hash, PartialOrd, Ord)]
+    Self {
+      selectors: prefix_selectors(&self.selectors),
+      media_queries: self.media_queries.as_ref().map(prefix_nested),
+      breakpoints: self.breakpoints.as_ref().map(prefix_nested),
+      themes: self.themes.as_ref().map(prefix_selectors),
+    }
+  }
+}
+
+#[derive(
+  Serialize, Deserialize, JsonSchema, Debug, PartialEq, Clone, Eq, Hash, PartialOrd, Ord,
+)]
 #[serde(untagged)]
 pub enum StyleRule {
   /// The rule has a value.
@@ -379,6 +1362,16 @@ pub enum StyleRule {
 }
 
 impl StyleRule {
+  pub(crate) fn with_variables_prefix(&self, prefix: &str) -> Self {
+    match self {
+      StyleRule::WithValue(name, value) => StyleRule::WithValue(
+        prefix_css_variable(name, prefix),
+        value.with_variables_prefix(prefix),
+      ),
+      StyleRule::Name(name) => StyleRule::Name(prefix_css_variable(name, prefix)),
+    }
+  }
+
   pub(crate) fn get_style_declaration(&self, css_value: Option<CssValue>) -> String {
     match self {
       StyleRule::WithValue(name, value) => format!("{}: {}", name, value.get_string()),
@@ -420,4 +1413,150 @@ mod tests {
     let json = serde_json::to_string(&config).unwrap();
     assert_eq!(config, serde_json::from_str(&json).unwrap());
   }
+
+  #[test]
+  fn nested_themes_are_scoped_by_their_parent() {
+    let mut config: UserConfig = serde_json::from_str(JSON_CONFIG).unwrap();
+    config.themes = serde_json::from_str(
+      r#"{
+        "light": { "default": true, "selector": "html.light" },
+        "dark": { "selector": "html.dark", "mediaQuery": "(prefers-color-scheme: dark)" },
+        "darkContrast": { "parent": "dark", "selector": ".contrast", "mediaQuery": "(prefers-contrast: more)" }
+      }"#,
+    )
+    .unwrap();
+
+    let target = |selector: &str, media_query: Option<&str>| ThemeTarget {
+      selector: selector.into(),
+      media_query: media_query.map(Into::into),
+    };
+
+    pretty_assertions::assert_eq!(
+      config.get_theme_targets("light"),
+      vec![target(":root", None), target("html.light", None)]
+    );
+    pretty_assertions::assert_eq!(
+      config.get_theme_targets("darkContrast"),
+      vec![
+        target(
+          ":root",
+          Some("(prefers-color-scheme: dark) and (prefers-contrast: more)")
+        ),
+        target(":root .contrast", Some("(prefers-color-scheme: dark)")),
+        target("html.dark", Some("(prefers-contrast: more)")),
+        target("html.dark .contrast", None),
+      ]
+    );
+  }
+
+  #[test]
+  fn scales_generate_values() {
+    let scale: Scale = serde_json::from_str(
+      r#"{ "start": 0, "end": 2, "step": 0.5, "multiplier": 0.25, "unit": "rem" }"#,
+    )
+    .unwrap();
+
+    insta::assert_debug_snapshot!(scale.to_values(), @r###"
+    {
+        "0": String(
+            "0rem",
+        ),
+        "0_5": String(
+            "0.125rem",
+        ),
+        "1": String(
+            "0.25rem",
+        ),
+        "1_5": String(
+            "0.375rem",
+        ),
+        "2": String(
+            "0.5rem",
+        ),
+    }
+    "###);
+  }
+
+  #[test]
+  fn modular_scales_multiply_by_the_ratio() {
+    let scale: Scale =
+      serde_json::from_str(r#"{ "start": -1, "end": 2, "ratio": 1.25, "unit": "rem" }"#).unwrap();
+
+    pretty_assertions::assert_eq!(
+      scale
+        .to_values()
+        .iter()
+        .map(|(name, value)| format!("{}: {}", name, value.get_string()))
+        .collect::<Vec<_>>(),
+      vec!["_1: 0.8rem", "0: 1rem", "1: 1.25rem", "2: 1.5625rem"]
+    );
+  }
+
+  #[test]
+  fn container_queries_can_name_the_container() {
+    let queries: IndexMap<String, ContainerQuery> = serde_json::from_str(
+      r#"{ "cqMd": "28rem", "cardWide": { "minWidth": "32rem", "container": "card" } }"#,
+    )
+    .unwrap();
+
+    pretty_assertions::assert_eq!(queries["cqMd"].get_query(), "(min-width: 28rem)");
+    pretty_assertions::assert_eq!(queries["cardWide"].get_query(), "card (min-width: 32rem)");
+  }
+
+  #[test]
+  fn fluid_values_use_the_breakpoints() {
+    let config: UserConfig = serde_json::from_str(JSON_CONFIG).unwrap();
+    let fluid: Fluid = serde_json::from_str(
+      r#"{ "values": { "xl": ["1.25rem", "1.5rem"], "gap": ["32px", "16px"], "auto": ["auto", "1rem"] } }"#,
+    )
+    .unwrap();
+
+    insta::assert_debug_snapshot!(fluid.to_values(&config.breakpoints), @r###"
+    {
+        "xl": String(
+            "clamp(1.25rem, 1.0714rem + 0.4464vw, 1.5rem)",
+        ),
+        "gap": String(
+            "clamp(16px, 2.7143rem - 1.7857vw, 32px)",
+        ),
+    }
+    "###);
+  }
+
+  #[test]
+  fn fluid_atom_values_use_the_breakpoints() {
+    let config: UserConfig = serde_json::from_str(JSON_CONFIG).unwrap();
+    let atom: Atom = serde_json::from_str(
+      r#"{ "styleRules": ["fontSize"], "fluid": { "values": { "xl": ["1.25rem", "1.5rem"] } } }"#,
+    )
+    .unwrap();
+
+    pretty_assertions::assert_eq!(
+      atom.to_atom_value(&config.breakpoints).values["xl"].get_string(),
+      "clamp(1.25rem, 1.0714rem + 0.4464vw, 1.5rem)"
+    );
+  }
+
+  #[test]
+  fn bundled_palettes_can_be_named() {
+    let palette: ColorPalette = serde_json::from_str(r#""openColor""#).unwrap();
+    assert_eq!(palette, ColorPalette::Named(PaletteName::OpenColor));
+  }
+
+  #[test]
+  fn composite_palettes_are_namespaced() {
+    let palette: ColorPalette = serde_json::from_str(
+      r##"[
+        "tailwind",
+        { "namespace": "oc", "palette": "openColor" },
+        { "black": "#111" }
+      ]"##,
+    )
+    .unwrap();
+    let map = palette.to_map();
+
+    assert_eq!(map["purple500"], "#a855f7");
+    assert_eq!(map["ocGray50"], "#f8f9fa");
+    assert_eq!(map["black"], "#111");
+  }
 }
@@ -0,0 +1,753 @@
+use std::fmt;
+
+use indexmap::{IndexMap, IndexSet};
+
+use crate::{constants::ROOT_SELECTOR, utils::get_css_variables_from_string};
+
+use super::{
+  color_expression::ColorResolver,
+  color_utils::{is_css_color, parse_rgb},
+  error::to_json_pointer,
+  user::{
+    parse_length, Atom, ColorPalette, CssValue, CssVariable, PaletteSource, StyleRule,
+    MAX_SCALE_STEPS,
+  },
+  Config,
+};
+
+/// How serious a `ConfigIssue` is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+  /// The configuration works but probably doesn't do what was intended.
+  Warning,
+
+  /// Part of the configuration can never be used.
+  Error,
+}
+
+/// A problem with the meaning of a configuration which parsed successfully.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigIssue {
+  pub severity: Severity,
+
+  /// A JSON pointer to the offending key, e.g. `/atoms/3/styleRules/0`.
+  pub path: String,
+
+  pub message: String,
+}
+
+impl ConfigIssue {
+  fn error(path: &str, message: String) -> Self {
+    Self {
+      severity: Severity::Error,
+      path: path.to_owned(),
+      message,
+    }
+  }
+
+  fn warning(path: &str, message: String) -> Self {
+    Self {
+      severity: Severity::Warning,
+      path: path.to_owned(),
+      message,
+    }
+  }
+
+  pub fn is_error(&self) -> bool {
+    matches!(self.severity, Severity::Error)
+  }
+}
+
+impl fmt::Display for ConfigIssue {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let severity = match self.severity {
+      Severity::Warning => "warning",
+      Severity::Error => "error",
+    };
+
+    write!(f, "{} at `{}`: {}", severity, self.path, self.message)
+  }
+}
+
+impl Config {
+  /// Check the configuration for mistakes which can't be caught while parsing.
+  ///
+  /// ```rust
+  /// use skribble_css::config::Config;
+  ///
+  /// let config = Config::default();
+  /// assert!(config.validate().iter().all(|issue| !issue.is_error()));
+  /// ```
+  pub fn validate(&self) -> Vec<ConfigIssue> {
+    let mut issues: Vec<ConfigIssue> = vec![];
+
+    self.validate_atoms(&mut issues);
+    self.validate_token_names(&mut issues);
+    self.validate_colors(&mut issues);
+    self.validate_css_variables(&mut issues);
+    self.validate_themes(&mut issues);
+
+    issues
+  }
+
+  /// Every atom must point to an existing style rule.
+  fn validate_atoms(&self, issues: &mut Vec<ConfigIssue>) {
+    for (index, atom) in self.user.atoms.iter().enumerate() {
+      let style_rules = match atom {
+        Atom::Color(atom) => &atom.style_rules,
+        Atom::Value(atom) => &atom.style_rules,
+        Atom::Scale(atom) => &atom.style_rules,
+        Atom::Fluid(atom) => &atom.style_rules,
+      };
+
+      if let Atom::Scale(atom) = atom {
+        let scale = &atom.scale;
+        let path = to_json_pointer(&["atoms", &index.to_string(), "scale"]);
+
+        if scale.step <= 0.0 {
+          issues.push(ConfigIssue::error(
+            &format!("{}/step", path),
+            "the step must be greater than `0`.".into(),
+          ));
+        } else if scale.end < scale.start {
+          issues.push(ConfigIssue::error(
+            &format!("{}/end", path),
+            format!(
+              "the end `{}` is before the start `{}`.",
+              scale.end, scale.start
+            ),
+          ));
+        } else if let Some(count) = scale
+          .get_step_count()
+          .filter(|count| *count > MAX_SCALE_STEPS)
+        {
+          issues.push(ConfigIssue::error(
+            &format!("{}/step", path),
+            format!(
+              "the scale has {} steps which is more than the limit of {}.",
+              count, MAX_SCALE_STEPS
+            ),
+          ));
+        }
+
+        if matches!(scale.ratio, Some(ratio) if ratio <= 0.0) {
+          issues.push(ConfigIssue::error(
+            &format!("{}/ratio", path),
+            "the ratio must be greater than `0`.".into(),
+          ));
+        }
+      }
+
+      if let Atom::Fluid(atom) = atom {
+        let fluid = &atom.fluid;
+        let path = to_json_pointer(&["atoms", &index.to_string(), "fluid"]);
+
+        if fluid.get_viewports(&self.user.breakpoints).is_none() {
+          issues.push(ConfigIssue::error(
+            &path,
+            "the viewports must be `px` or `rem` lengths with `maxViewport` larger than \
+             `minViewport`."
+              .into(),
+          ));
+        }
+
+        for (name, (min, max)) in fluid.values.iter() {
+          for (position, value) in [(0, min), (1, max)] {
+            if parse_length(&value.get_string()).is_none() {
+              issues.push(ConfigIssue::error(
+                &format!(
+                  "{}{}",
+                  path,
+                  to_json_pointer(&["values", name, &position.to_string()])
+                ),
+                format!("`{}` is not a `px` or `rem` length.", value.get_string()),
+              ));
+            }
+          }
+        }
+      }
+
+      for (rule_index, rule) in style_rules.iter().enumerate() {
+        if !self.user.style_rules.contains_key(rule) {
+          issues.push(ConfigIssue::error(
+            &to_json_pointer(&[
+              "atoms",
+              &index.to_string(),
+              "styleRules",
+              &rule_index.to_string(),
+            ]),
+            format!("`{}` is not defined in `styleRules`.", rule),
+          ));
+        }
+      }
+    }
+  }
+
+  /// Tokens are matched in the order breakpoints, media queries, feature
+  /// queries, container queries, parent modifiers, modifiers and then atoms. A
+  /// name which is used by an earlier category can never match in a later one.
+  fn validate_token_names(&self, issues: &mut Vec<ConfigIssue>) {
+    let mut claimed: IndexMap<String, &str> = IndexMap::new();
+
+    for name in self.breakpoints.keys() {
+      claimed.insert(name.to_owned(), "breakpoint");
+    }
+
+    let mut media_query_groups: IndexMap<&str, usize> = IndexMap::new();
+
+    for (group, media_queries) in self.user.media_queries.groups().into_iter().enumerate() {
+      for name in media_queries.keys() {
+        let path = self.user.media_queries.get_path(name);
+
+        if let Some(first_group) = media_query_groups.get(name.as_str()) {
+          issues.push(ConfigIssue::warning(
+            &to_json_pointer(&["mediaQueries", &group.to_string(), name]),
+            format!(
+              "the media query `{}` is already defined in group {} and will be ignored.",
+              name, first_group
+            ),
+          ));
+
+          continue;
+        }
+
+        media_query_groups.insert(name, group);
+        claim_token(&mut claimed, issues, name, "media query", &path);
+      }
+    }
+
+    for name in self.user.supports.keys() {
+      claim_token(
+        &mut claimed,
+        issues,
+        name,
+        "feature query",
+        &to_json_pointer(&["supports", name]),
+      );
+    }
+
+    for name in self.user.container_queries.keys() {
+      claim_token(
+        &mut claimed,
+        issues,
+        name,
+        "container query",
+        &to_json_pointer(&["containerQueries", name]),
+      );
+    }
+
+    for name in self.user.parent_modifiers.keys() {
+      claim_token(
+        &mut claimed,
+        issues,
+        name,
+        "parent modifier",
+        &to_json_pointer(&["parentModifiers", name]),
+      );
+    }
+
+    let mut modifier_groups: IndexMap<&str, usize> = IndexMap::new();
+
+    for (group, modifiers) in self.user.modifiers.iter().enumerate() {
+      for name in modifiers.keys() {
+        let path = to_json_pointer(&["modifiers", &group.to_string(), name]);
+
+        if let Some(first_group) = modifier_groups.get(name.as_str()) {
+          issues.push(ConfigIssue::warning(
+            &path,
+            format!(
+              "the modifier `{}` is already defined in group {} and will be overridden.",
+              name, first_group
+            ),
+          ));
+
+          continue;
+        }
+
+        modifier_groups.insert(name, group);
+        claim_token(&mut claimed, issues, name, "modifier", &path);
+      }
+    }
+
+    for (index, atom) in self.user.atoms.iter().enumerate() {
+      let style_rules = match atom {
+        Atom::Color(atom) => &atom.style_rules,
+        Atom::Value(atom) => &atom.style_rules,
+        Atom::Scale(atom) => &atom.style_rules,
+        Atom::Fluid(atom) => &atom.style_rules,
+      };
+
+      for (rule_index, rule) in style_rules.iter().enumerate() {
+        if let Some(category) = claimed.get(rule) {
+          issues.push(ConfigIssue::error(
+            &to_json_pointer(&[
+              "atoms",
+              &index.to_string(),
+              "styleRules",
+              &rule_index.to_string(),
+            ]),
+            format!(
+              "the atom `{}` is shadowed by the {} with the same name.",
+              rule, category
+            ),
+          ));
+        }
+      }
+    }
+  }
+
+  /// Colors must either be valid css colors or names from the palette.
+  fn validate_colors(&self, issues: &mut Vec<ConfigIssue>) {
+    for (path, seed) in get_palette_seeds("/palette", &self.user.palette) {
+      if parse_rgb(seed).is_none() {
+        issues.push(ConfigIssue::error(
+          &path,
+          format!(
+            "`{}` is not a color which shades can be generated from.",
+            seed
+          ),
+        ));
+      }
+    }
+
+    let palette = self.user.palette.to_map();
+    let resolver = ColorResolver::new(&palette, &self.user.colors);
+
+    for (name, color) in self.user.colors.iter() {
+      let path = to_json_pointer(&["colors", name]);
+
+      for (value_path, selector, value) in get_css_variable_values(&path, color) {
+        let value = value.get_string();
+
+        match resolver.resolve(name, selector, &value) {
+          Ok(Some(_)) => continue,
+          Ok(None) => {}
+          Err(message) => {
+            issues.push(ConfigIssue::error(&value_path, message));
+            continue;
+          }
+        }
+
+        if !palette.contains_key(&value) && !is_css_color(&value) {
+          issues.push(ConfigIssue::error(
+            &value_path,
+            format!(
+              "`{}` is neither a css color nor a color in the palette.",
+              value
+            ),
+          ));
+        }
+      }
+    }
+  }
+
+  /// Every `var()` used by a style rule or shorthand without a fallback must
+  /// refer to a declared variable.
+  fn validate_css_variables(&self, issues: &mut Vec<ConfigIssue>) {
+    let mut declared: IndexSet<String> = self.css_variables.keys().cloned().collect();
+
+    for atom in self.user.atoms.iter() {
+      if let Atom::Color(atom) = atom {
+        declared.insert(atom.colors.opacity.clone());
+      }
+    }
+
+    let sections = [
+      ("styleRules", &self.user.style_rules),
+      ("shorthand", &self.user.shorthand),
+    ];
+
+    for (_, rules) in sections {
+      for rule in rules.values().flatten() {
+        let name = match rule {
+          StyleRule::WithValue(name, _) => name,
+          StyleRule::Name(name) => name,
+        };
+
+        if name.starts_with("--") {
+          declared.insert(name.clone());
+        }
+      }
+    }
+
+    for (section, rules) in sections {
+      for (name, rules) in rules.iter() {
+        for (index, rule) in rules.iter().enumerate() {
+          let value = match rule {
+            StyleRule::WithValue(_, value) => value.get_string(),
+            StyleRule::Name(_) => continue,
+          };
+
+          for variable in get_css_variables_from_string(&value) {
+            if !declared.contains(&variable) {
+              issues.push(ConfigIssue::error(
+                &to_json_pointer(&[section, name, &index.to_string()]),
+                format!(
+                  "`var({})` is never declared. Add it to `variables` or provide a fallback.",
+                  variable
+                ),
+              ));
+            }
+          }
+        }
+      }
+    }
+  }
+
+  /// Themes must be applied somewhere, only one can be the default and values
+  /// can only be provided for themes which exist.
+  fn validate_themes(&self, issues: &mut Vec<ConfigIssue>) {
+    let themes = &self.user.themes;
+    let mut default_theme: Option<&String> = None;
+
+    for (name, theme) in themes.iter() {
+      let path = to_json_pointer(&["themes", name]);
+
+      if theme.default {
+        match default_theme {
+          Some(existing) => issues.push(ConfigIssue::error(
+            &format!("{}/default", path),
+            format!(
+              "only one theme can be the default and `{}` already is.",
+              existing
+            ),
+          )),
+          None => default_theme = Some(name),
+        }
+      } else if theme.selector.is_none() && theme.media_query.is_none() {
+        issues.push(ConfigIssue::error(
+          &path,
+          "the theme is never applied. Add a `selector` or a `mediaQuery`.".into(),
+        ));
+      }
+
+      let mut chain = vec![name.as_str()];
+      let mut parent = theme.parent.as_deref();
+
+      while let Some(parent_name) = parent {
+        if chain.contains(&parent_name) {
+          chain.push(parent_name);
+          issues.push(ConfigIssue::error(
+            &format!("{}/parent", path),
+            format!("circular theme parents: {}", chain.join(" -> ")),
+          ));
+          break;
+        }
+
+        match themes.get(parent_name) {
+          Some(parent_theme) => {
+            chain.push(parent_name);
+            parent = parent_theme.parent.as_deref();
+          }
+          None => {
+            // Only report the missing parent on the theme which names it.
+            if chain.len() == 1 {
+              issues.push(ConfigIssue::error(
+                &format!("{}/parent", path),
+                format!("`{}` is not defined in `themes`.", parent_name),
+              ));
+            }
+            break;
+          }
+        }
+      }
+    }
+
+    let sections = [
+      ("colors", &self.user.colors),
+      ("variables", &self.user.variables),
+    ];
+
+    for (section, variables) in sections {
+      for (name, variable) in variables.iter() {
+        let variable_themes = match variable {
+          CssVariable::Object(object) => object.themes.iter().flatten(),
+          CssVariable::Value(_) => continue,
+        };
+
+        for (theme, _) in variable_themes {
+          if !themes.contains_key(theme) {
+            issues.push(ConfigIssue::error(
+              &to_json_pointer(&[section, name, "themes", theme]),
+              format!("`{}` is not defined in `themes`.", theme),
+            ));
+          }
+        }
+      }
+    }
+  }
+}
+
+fn claim_token<'a>(
+  claimed: &mut IndexMap<String, &'a str>,
+  issues: &mut Vec<ConfigIssue>,
+  name: &str,
+  category: &'a str,
+  path: &str,
+) {
+  match claimed.get(name) {
+    Some(existing) => issues.push(ConfigIssue::error(
+      path,
+      format!(
+        "the {} `{}` is shadowed by the {} with the same name.",
+        category, name, existing
+      ),
+    )),
+    None => {
+      claimed.insert(name.to_owned(), category);
+    }
+  }
+}
+
+/// Get the seeds of every generated palette along with their paths.
+fn get_palette_seeds<'a>(path: &str, palette: &'a ColorPalette) -> Vec<(String, &'a str)> {
+  match palette {
+    ColorPalette::Generated(generated) => generated
+      .seeds
+      .iter()
+      .map(|(name, seed)| {
+        let seed_path = format!("{}{}", path, to_json_pointer(&["seeds", name]));
+        (seed_path, seed.as_str())
+      })
+      .collect(),
+    ColorPalette::Composite(sources) => sources
+      .iter()
+      .enumerate()
+      .flat_map(|(index, source)| match source {
+        PaletteSource::Namespaced(namespaced) => {
+          get_palette_seeds(&format!("{}/{}/palette", path, index), &namespaced.palette)
+        }
+        PaletteSource::Palette(palette) => {
+          get_palette_seeds(&format!("{}/{}", path, index), palette)
+        }
+      })
+      .collect(),
+    _ => vec![],
+  }
+}
+
+/// Get every value of a css variable along with its path and selector.
+fn get_css_variable_values<'a>(
+  path: &str,
+  variable: &'a CssVariable,
+) -> Vec<(String, &'a str, &'a CssValue)> {
+  let mut values = vec![];
+
+  match variable {
+    CssVariable::Value(value) => values.push((path.to_owned(), ROOT_SELECTOR, value)),
+    CssVariable::Object(object) => {
+      for (selector, value) in object.selectors.iter() {
+        values.push((
+          format!("{}{}", path, to_json_pointer(&["selectors", selector])),
+          selector.as_str(),
+          value,
+        ));
+      }
+
+      for (theme, value) in object.themes.iter().flatten() {
+        let theme_path = format!("{}{}", path, to_json_pointer(&["themes", theme]));
+        values.push((theme_path, theme.as_str(), value));
+      }
+
+      let nested = [
+        ("mediaQueries", &object.media_queries),
+        ("breakpoints", &object.breakpoints),
+      ];
+
+      for (key, container) in nested {
+        for (name, selectors) in container.iter().flatten() {
+          for (selector, value) in selectors.iter() {
+            values.push((
+              format!("{}{}", path, to_json_pointer(&[key, name, selector])),
+              selector.as_str(),
+              value,
+            ));
+          }
+        }
+      }
+    }
+  }
+
+  values
+}
+
+#[cfg(test)]
+mod tests {
+  use serde_json::{json, Value};
+
+  use crate::{config::Config, constants::JSON_CONFIG};
+
+  use super::*;
+
+  fn validate(update: impl Fn(&mut Value)) -> Vec<ConfigIssue> {
+    let mut json: Value = serde_json::from_str(JSON_CONFIG).unwrap();
+    update(&mut json);
+
+    Config::new(&json.to_string()).unwrap().validate()
+  }
+
+  #[test]
+  fn default_config_is_valid() {
+    pretty_assertions::assert_eq!(Config::default().validate(), vec![]);
+  }
+
+  #[test]
+  fn invalid_themes() {
+    let issues = validate(|json| {
+      json["themes"] = json!({
+        "light": { "default": true },
+        "dark": { "default": true, "parent": "night" },
+        "contrast": { "parent": "contrast", "mediaQuery": "(prefers-contrast: more)" },
+        "unused": {}
+      });
+      json["colors"]["text"] = json!({ "themes": { "light": "gray900", "sepia": "amber900" } });
+    });
+
+    insta::assert_debug_snapshot!(issues);
+  }
+
+  #[test]
+  fn invalid_scales() {
+    let issues = validate(|json| {
+      json["atoms"][0] =
+        json!({ "styleRules": ["font"], "scale": { "start": 4, "end": 0, "ratio": -1 } })
+    });
+
+    pretty_assertions::assert_eq!(
+      issues,
+      vec![
+        ConfigIssue::error(
+          "/atoms/0/scale/end",
+          "the end `0` is before the start `4`.".into()
+        ),
+        ConfigIssue::error(
+          "/atoms/0/scale/ratio",
+          "the ratio must be greater than `0`.".into()
+        ),
+      ]
+    );
+  }
+
+  #[test]
+  fn scales_with_too_many_steps() {
+    let issues = validate(|json| {
+      json["atoms"][0] =
+        json!({ "styleRules": ["font"], "scale": { "start": 0, "end": 100, "step": 0.01 } })
+    });
+
+    pretty_assertions::assert_eq!(
+      issues,
+      vec![ConfigIssue::error(
+        "/atoms/0/scale/step",
+        "the scale has 10001 steps which is more than the limit of 1000.".into()
+      )]
+    );
+  }
+
+  #[test]
+  fn invalid_fluid_values() {
+    let issues = validate(|json| {
+      json["atoms"][0] = json!({
+        "styleRules": ["fontSize"],
+        "fluid": { "minViewport": "80em", "values": { "xl": ["1rem", "2vw"] } }
+      })
+    });
+
+    insta::assert_debug_snapshot!(issues);
+  }
+
+  #[test]
+  fn missing_style_rules() {
+    let issues = validate(|json| json["atoms"][0]["styleRules"] = json!(["fonts"]));
+
+    pretty_assertions::assert_eq!(
+      issues,
+      vec![ConfigIssue::error(
+        "/atoms/0/styleRules/0",
+        "`fonts` is not defined in `styleRules`.".into()
+      )]
+    );
+  }
+
+  #[test]
+  fn shadowed_modifiers() {
+    let issues = validate(|json| json["modifiers"][0] = json!({ "md": ["&:hover"] }));
+
+    pretty_assertions::assert_eq!(
+      issues,
+      vec![ConfigIssue::error(
+        "/modifiers/0/md",
+        "the modifier `md` is shadowed by the breakpoint with the same name.".into()
+      )]
+    );
+  }
+
+  #[test]
+  fn token_paths_are_escaped_json_pointers() {
+    let issues = validate(|json| {
+      json["supports"]["grid/subgrid~"] = json!("(grid-template-rows: subgrid)");
+      json["parentModifiers"]["grid/subgrid~"] = json!([".subgrid &"]);
+    });
+
+    pretty_assertions::assert_eq!(
+      issues,
+      vec![ConfigIssue::error(
+        "/parentModifiers/grid~1subgrid~0",
+        "the parent modifier `grid/subgrid~` is shadowed by the feature query with the same name."
+          .into()
+      )]
+    );
+  }
+
+  #[test]
+  fn unknown_palette_colors() {
+    let issues = validate(|json| json["colors"]["primary"] = json!("purple550"));
+
+    pretty_assertions::assert_eq!(
+      issues,
+      vec![ConfigIssue::error(
+        "/colors/primary",
+        "`purple550` is neither a css color nor a color in the palette.".into()
+      )]
+    );
+  }
+
+  #[test]
+  fn paths_are_escaped_json_pointers() {
+    let issues = validate(|json| {
+      json["colors"]["primary"] = json!({ "selectors": { "[data-mode~=a/b]": "purple550" } });
+    });
+
+    pretty_assertions::assert_eq!(
+      issues,
+      vec![ConfigIssue::error(
+        "/colors/primary/selectors/[data-mode~0=a~1b]",
+        "`purple550` is neither a css color nor a color in the palette.".into()
+      )]
+    );
+  }
+
+  #[test]
+  fn invalid_palette_seeds() {
+    let issues = validate(|json| {
+      json["palette"] = json!(["tailwind", { "seeds": { "brand": "violet950" } }]);
+    });
+
+    pretty_assertions::assert_eq!(
+      issues,
+      vec![ConfigIssue::error(
+        "/palette/1/seeds/brand",
+        "`violet950` is not a color which shades can be generated from.".into()
+      )]
+    );
+  }
+
+  #[test]
+  fn undeclared_css_variables() {
+    let issues = validate(|json| {
+      json["shorthand"]["block"] = json!([["display", "var(--display)"]]);
+    });
+
+    insta::assert_snapshot!(issues[0].to_string(), @"error at `/shorthand/block/0`: `var(--sk-display)` is never declared. Add it to `variables` or provide a fallback.");
+  }
+}
@@ -3,3 +3,4 @@ pub const ROOT_SELECTOR: &str = ":root";
 pub const PALETTE_TAILWIND: &str = include_str!("config/_palette_tailwind.json");
 pub const PALETTE_OPEN_COLOR: &str = include_str!("config/_palette_open_color.json");
 pub const JSON_CONFIG: &str = include_str!("config/_config_user.json");
+pub const PREFLIGHT: &str = include_str!("config/_preflight.json");
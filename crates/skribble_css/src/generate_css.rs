@@ -2,12 +2,18 @@ use indexmap::{IndexMap, IndexSet};
 
 use crate::{
   config::{
-    user::{BreakpointHelper, CssValue, MediaQueries},
+    user::{
+      BreakpointHelper, ContainerQuery, Css, CssValue, FontFace, FontSource, Keyframes, Layers,
+      MediaQueries, ThemeTarget,
+    },
     Config,
   },
-  constants::INDENTATION,
+  constants::{INDENTATION, PREFLIGHT},
   scanner::class_name::ClassName,
-  utils::indent,
+  utils::{
+    get_css_variables_from_string, get_font_families_from_declarations,
+    get_names_from_declarations, indent,
+  },
 };
 
 /// Transform the tokens and configuration into a valid css string which can be
@@ -18,12 +24,100 @@ use crate::{
 /// `just_in_time` is a boolean which determines if the css should be
 /// generated based on the class_names found.
 pub fn generate_css(config: &Config, class_names: &[&ClassName]) -> String {
-  let mut breakpoint_map: IndexMap<Option<String>, Vec<&ClassName>> = IndexMap::new();
-
   let breakpoints = config.user.breakpoints.to_breakpoints();
-  let css_variable_names: IndexSet<String> = get_all_css_variables_used(class_names);
+  let base_style_sheets = get_base_style_sheets(config);
+  let base_styles = get_base_styles(&base_style_sheets);
+  let base_declarations = get_declarations_from_styles(&base_style_sheets);
+  let mut css_variable_names: IndexSet<String> = get_all_css_variables_used(class_names);
+  css_variable_names.extend(get_css_variables_from_string(&base_styles));
+  let mut keyframe_names: IndexSet<String> = get_all_keyframes_used(class_names);
+  keyframe_names.extend(get_names_from_declarations(
+    &base_declarations,
+    config.user.keyframes.keys(),
+  ));
+  let mut font_families: IndexSet<String> = get_all_font_families_used(class_names);
+  font_families.extend(get_font_families_from_declarations(
+    &base_declarations,
+    config
+      .user
+      .font_faces
+      .iter()
+      .map(|font_face| &font_face.family),
+  ));
+  let font_faces = get_font_face_declarations(&config.user.font_faces, &font_families);
+  let keyframes = get_keyframes_declarations(&config.user.keyframes, &keyframe_names);
+
+  let css_list = match &config.user.options.layers {
+    Some(layers) => {
+      let no_variables: IndexSet<String> = IndexSet::new();
+      let layer_names = get_layer_names(layers);
+      let mut layer_map: IndexMap<&String, Vec<&ClassName>> =
+        layer_names.iter().map(|name| (*name, vec![])).collect();
+
+      for class_name in class_names {
+        if let Some(class_name_list) = layer_map.get_mut(get_layer_name(class_name, layers)) {
+          class_name_list.push(*class_name);
+        }
+      }
+
+      let variables = create_css_output(
+        config,
+        &css_variable_names,
+        breakpoints.clone(),
+        IndexMap::new(),
+      );
+      let mut css_list = vec![
+        font_faces,
+        format!(
+          "@layer {};",
+          layer_names
+            .iter()
+            .map(|name| layers.get_qualified_name(name))
+            .collect::<Vec<_>>()
+            .join(", ")
+        ),
+        get_css_from_layer(layers, &layers.variables, &variables),
+        get_css_from_layer(layers, &layers.base, &base_styles),
+      ];
+
+      for (name, class_name_list) in layer_map.iter() {
+        let output = create_css_output(
+          config,
+          &no_variables,
+          breakpoints.clone(),
+          group_by_breakpoint(class_name_list),
+        );
+        css_list.push(get_css_from_layer(layers, name, &output));
+      }
+
+      css_list.push(keyframes);
+      css_list
+    }
+    None => vec![
+      font_faces,
+      base_styles,
+      create_css_output(
+        config,
+        &css_variable_names,
+        breakpoints,
+        group_by_breakpoint(class_names),
+      ),
+      keyframes,
+    ],
+  };
+
+  css_list
+    .into_iter()
+    .filter(|css| !css.trim().is_empty())
+    .collect::<Vec<_>>()
+    .join("\n\n")
+}
+
+fn group_by_breakpoint<'a>(
+  class_names: &[&'a ClassName],
+) -> IndexMap<Option<String>, Vec<&'a ClassName<'a>>> {
+  let mut breakpoint_map: IndexMap<Option<String>, Vec<&ClassName>> = IndexMap::new();
 
-  // Group class_names by breakpoints.
   for class_name in class_names {
     let breakpoint = &class_name.breakpoint;
 
@@ -38,7 +132,227 @@ pub fn generate_css(config: &Config, class_names: &[&ClassName]) -> String {
     }
   }
 
-  create_css_output(config, &css_variable_names, breakpoints, breakpoint_map)
+  breakpoint_map
+}
+
+/// The layer names in the order they are declared.
+fn get_layer_names(layers: &Layers) -> Vec<&String> {
+  vec![
+    &layers.variables,
+    &layers.base,
+    &layers.shorthand,
+    &layers.atoms,
+    &layers.modifiers,
+    &layers.parent_modifiers,
+    &layers.container_queries,
+    &layers.supports,
+    &layers.media_queries,
+    &layers.breakpoints,
+  ]
+}
+
+/// Class names are placed in the layer of their highest variant tier.
+fn get_layer_name<'a>(class_name: &ClassName, layers: &'a Layers) -> &'a String {
+  if class_name.breakpoint.is_some() {
+    &layers.breakpoints
+  } else if !class_name.media_queries.is_empty() {
+    &layers.media_queries
+  } else if class_name.supports.is_some() {
+    &layers.supports
+  } else if class_name.container_query.is_some() {
+    &layers.container_queries
+  } else if class_name.parent_modifier.is_some() {
+    &layers.parent_modifiers
+  } else if !class_name.modifiers.is_empty() {
+    &layers.modifiers
+  } else if class_name.shorthand.is_some() {
+    &layers.shorthand
+  } else {
+    &layers.atoms
+  }
+}
+
+fn get_css_from_layer(layers: &Layers, name: &str, css: &str) -> String {
+  if css.trim().is_empty() {
+    return "".into();
+  }
+
+  format!(
+    "@layer {} {{\n{}\n}}",
+    layers.get_qualified_name(name),
+    indent(css, INDENTATION)
+  )
+}
+
+/// Generate a `<link rel="preload">` for every font face with `preload` set
+/// which is used by the class names. The links are written to a separate file
+/// to be included in the `<head>` of the document.
+pub fn generate_font_preloads(config: &Config, class_names: &[&ClassName]) -> String {
+  let font_families = get_all_font_families_used(class_names);
+
+  config
+    .user
+    .font_faces
+    .iter()
+    .filter(|font_face| font_face.preload && font_families.contains(&font_face.family))
+    .filter_map(get_font_preload_link)
+    .collect::<Vec<_>>()
+    .join("\n")
+}
+
+fn get_all_font_families_used(class_names: &[&ClassName]) -> IndexSet<String> {
+  let mut font_families: IndexSet<String> = IndexSet::new();
+
+  for class_name in class_names {
+    font_families.extend(class_name.font_families());
+  }
+
+  font_families
+}
+
+/// Write the `@font-face` rules for the used families in the order of the
+/// configuration.
+fn get_font_face_declarations(font_faces: &[FontFace], font_families: &IndexSet<String>) -> String {
+  let mut declarations: Vec<String> = Vec::new();
+
+  for font_face in font_faces {
+    if !font_families.contains(&font_face.family) {
+      continue;
+    }
+
+    let src: Vec<String> = font_face.src.iter().map(get_font_source).collect();
+    let mut properties = vec![
+      format!("font-family: \"{}\";", font_face.family),
+      format!("src: {};", src.join(", ")),
+    ];
+    let optional_properties = [
+      (
+        "font-weight",
+        font_face.weight.as_ref().map(CssValue::get_string),
+      ),
+      ("font-style", font_face.style.clone()),
+      ("font-display", font_face.display.clone()),
+      ("unicode-range", font_face.unicode_range.clone()),
+    ];
+
+    for (property, value) in optional_properties {
+      if let Some(value) = value {
+        properties.push(format!("{}: {};", property, value));
+      }
+    }
+
+    declarations.push(format!(
+      "@font-face {{\n{}\n}}",
+      indent(&properties.join("\n"), INDENTATION)
+    ));
+  }
+
+  declarations.join("\n\n")
+}
+
+fn get_font_source(source: &FontSource) -> String {
+  match source {
+    FontSource::Url(url) | FontSource::Object { url, format: None } => format!("url(\"{}\")", url),
+    FontSource::Object {
+      url,
+      format: Some(format),
+    } => format!("url(\"{}\") format(\"{}\")", url, format),
+    FontSource::Local { local } => format!("local(\"{}\")", local),
+  }
+}
+
+/// The preload link uses the first `url` source. The type is taken from the
+/// `format` or the file extension.
+fn get_font_preload_link(font_face: &FontFace) -> Option<String> {
+  let (url, format) = font_face.src.iter().find_map(|source| match source {
+    FontSource::Url(url) => Some((url, None)),
+    FontSource::Object { url, format } => Some((url, format.as_deref())),
+    FontSource::Local { .. } => None,
+  })?;
+  let font_type = format
+    .or_else(|| url.rsplit_once('.').map(|(_, extension)| extension))
+    .map(|format| format!(" type=\"font/{}\"", format))
+    .unwrap_or_default();
+
+  Some(format!(
+    "<link rel=\"preload\" href=\"{}\" as=\"font\"{} crossorigin>",
+    url, font_type
+  ))
+}
+
+/// Get the preflight, when enabled, followed by the `globalStyles`.
+fn get_base_style_sheets(config: &Config) -> Vec<IndexMap<String, Css>> {
+  let mut style_sheets: Vec<IndexMap<String, Css>> = Vec::new();
+
+  if config.user.options.preflight {
+    style_sheets.push(serde_json::from_str(PREFLIGHT).unwrap());
+  }
+
+  style_sheets.push(config.user.global_styles.clone());
+  style_sheets
+}
+
+fn get_base_styles(style_sheets: &[IndexMap<String, Css>]) -> String {
+  style_sheets
+    .iter()
+    .map(get_css_from_styles)
+    .filter(|css| !css.is_empty())
+    .collect::<Vec<_>>()
+    .join("\n\n")
+}
+
+/// Get every declaration of the styles so that the keyframes and font families
+/// they use can be found.
+fn get_declarations_from_styles(style_sheets: &[IndexMap<String, Css>]) -> String {
+  let mut declarations: Vec<String> = Vec::new();
+
+  for css in style_sheets.iter().flat_map(|styles| styles.values()) {
+    let styles: Vec<&IndexMap<String, CssValue>> = match css {
+      Css::Style(style) => vec![style],
+      Css::Query(query) => query.values().collect(),
+    };
+
+    for (property, value) in styles.into_iter().flatten() {
+      declarations.push(format!("{}: {};", property, value.get_string()));
+    }
+  }
+
+  declarations.join("\n")
+}
+
+fn get_css_from_styles(styles: &IndexMap<String, Css>) -> String {
+  let get_css_from_style = |selector: &str, style: &IndexMap<String, CssValue>| {
+    let declarations: Vec<String> = style
+      .iter()
+      .map(|(property, value)| format!("{}: {};", property, value.get_string()))
+      .collect();
+
+    format!(
+      "{} {{\n{}\n}}",
+      selector,
+      indent(&declarations.join("\n"), INDENTATION)
+    )
+  };
+
+  styles
+    .iter()
+    .map(|(key, css)| match css {
+      Css::Style(style) => get_css_from_style(key, style),
+      Css::Query(query) => {
+        let css_list: Vec<String> = query
+          .iter()
+          .map(|(selector, style)| get_css_from_style(selector, style))
+          .collect();
+
+        format!(
+          "{} {{\n{}\n}}",
+          key,
+          indent(&css_list.join("\n\n"), INDENTATION)
+        )
+      }
+    })
+    .collect::<Vec<_>>()
+    .join("\n\n")
 }
 
 fn get_all_css_variables_used(class_names: &[&ClassName]) -> IndexSet<String> {
@@ -51,13 +365,61 @@ fn get_all_css_variables_used(class_names: &[&ClassName]) -> IndexSet<String> {
   css_variable_names
 }
 
+fn get_all_keyframes_used(class_names: &[&ClassName]) -> IndexSet<String> {
+  let mut keyframe_names: IndexSet<String> = IndexSet::new();
+
+  for class_name in class_names {
+    keyframe_names.extend(class_name.keyframes());
+  }
+
+  keyframe_names
+}
+
+/// Write the `@keyframes` for the used names in the order of the configuration.
+fn get_keyframes_declarations(
+  keyframes: &IndexMap<String, Keyframes>,
+  keyframe_names: &IndexSet<String>,
+) -> String {
+  let mut declarations: Vec<String> = Vec::new();
+
+  for (name, steps) in keyframes.iter() {
+    if !keyframe_names.contains(name) {
+      continue;
+    }
+
+    let steps: Vec<String> = steps
+      .iter()
+      .map(|(step, properties)| {
+        let properties: Vec<String> = properties
+          .iter()
+          .map(|(property, value)| format!("{}: {};", property, value.get_string()))
+          .collect();
+
+        format!(
+          "{} {{\n{}\n}}",
+          step,
+          indent(&properties.join("\n"), INDENTATION)
+        )
+      })
+      .collect();
+
+    declarations.push(format!(
+      "@keyframes {} {{\n{}\n}}",
+      name,
+      indent(&steps.join("\n\n"), INDENTATION)
+    ));
+  }
+
+  declarations.join("\n\n")
+}
+
 fn create_css_output(
   config: &Config,
   css_variable_names: &IndexSet<String>,
-  breakpoints: IndexMap<Option<String>, CssValue>,
+  breakpoints: IndexMap<Option<String>, String>,
   breakpoint_map: IndexMap<Option<String>, Vec<&ClassName>>,
 ) -> String {
-  let (css_variable_selectors, css_variable_breakpoints, css_variable_queries) =
+  let (css_variable_selectors, css_variable_breakpoints, css_variable_queries, css_variable_themes) =
     create_css_variable_containers(config, css_variable_names);
   println!(
     "css_variable_selectors: {:?}\ncss_variable_breakpoints: {:?}\ncss_variable_queries: {:?}\n\n",
@@ -66,7 +428,7 @@ fn create_css_output(
 
   let mut styles: Vec<String> = Vec::new();
 
-  for (breakpoint_name, css_value) in breakpoints.iter() {
+  for (breakpoint_name, breakpoint_query) in breakpoints.iter() {
     let mut css_list: Vec<String> = vec![];
     let empty_class_name_list: Vec<&ClassName> = vec![];
     let class_name_list = breakpoint_map
@@ -75,6 +437,7 @@ fn create_css_output(
     let media_query_string = create_media_query_string(
       class_name_list,
       &config.user.media_queries,
+      &(&config.user.supports, &config.user.container_queries),
       &css_variable_queries,
       breakpoint_name,
     );
@@ -97,21 +460,31 @@ fn create_css_output(
           styles.push(get_css_from_breakpoints(
             &css_list,
             breakpoint_name,
-            css_value,
+            breakpoint_query,
           ));
         }
       }
       None => {
         let variable_string = get_css_variable_declarations(&css_variable_selectors);
-        if !variable_string.trim().is_empty() {
-          css_list.insert(0, variable_string);
+        let theme_strings = css_variable_themes.iter().map(|(media_query, selectors)| {
+          get_css_from_media_query(&[get_css_variable_declarations(selectors)], media_query)
+        });
+
+        for (index, variable_string) in [variable_string]
+          .into_iter()
+          .chain(theme_strings)
+          .enumerate()
+        {
+          if !variable_string.trim().is_empty() {
+            css_list.insert(index, variable_string);
+          }
         }
 
         if !css_list.is_empty() {
           styles.push(get_css_from_breakpoints(
             &css_list,
             breakpoint_name,
-            css_value,
+            breakpoint_query,
           ));
         }
       }
@@ -124,39 +497,33 @@ fn create_css_output(
 fn create_media_query_string(
   class_name_list: &[&ClassName],
   media_queries: &MediaQueries,
+  feature_queries: &FeatureQueries,
   css_variable_queries: &IndexMap<String, CssVariableSelectors>,
   breakpoint: &Option<String>,
 ) -> String {
-  let mut css_without_queries: Vec<String> = vec![];
-  let mut queries_map: IndexMap<String, Vec<String>> = IndexMap::new();
+  let mut class_names_without_queries: Vec<&ClassName> = vec![];
+  let mut queries_map: IndexMap<&[String], Vec<&ClassName>> = IndexMap::new();
   let mut styles: Vec<String> = Vec::new();
 
   for class_name in class_name_list {
-    match &class_name.media_query {
-      Some(query) => match queries_map.get_mut(query) {
-        Some(css_list) => {
-          css_list.push(class_name.get_css());
-        }
-        None => {
-          let css_list = vec![class_name.get_css()];
-          queries_map.insert(query.to_owned(), css_list);
-        }
-      },
-      None => css_without_queries.push(class_name.get_css()),
+    match class_name.media_queries.as_slice() {
+      [] => class_names_without_queries.push(*class_name),
+      names => queries_map.entry(names).or_default().push(*class_name),
     }
   }
 
-  let initial_styles = css_without_queries.join("\n\n");
+  let initial_styles =
+    get_css_with_feature_queries(&class_names_without_queries, feature_queries).join("\n\n");
 
   if !initial_styles.trim().is_empty() {
     styles.push(initial_styles);
   }
 
-  for (query_name, media_query) in media_queries {
-    let mut default_css_list: Vec<String> = vec![];
-    let css_list = queries_map
-      .get_mut(query_name)
-      .unwrap_or(&mut default_css_list);
+  for (query_name, media_query) in media_queries.iter() {
+    let mut css_list = match queries_map.get([query_name.to_owned()].as_slice()) {
+      Some(class_names) => get_css_with_feature_queries(class_names, feature_queries),
+      None => vec![],
+    };
 
     if breakpoint.is_none() {
       if let Some(css_vars) = css_variable_queries.get(query_name) {
@@ -172,23 +539,133 @@ fn create_media_query_string(
       continue;
     }
 
-    let query_styles = get_css_from_media_query(css_list, media_query);
+    let query_styles = get_css_from_media_query(&css_list, media_query);
 
     if !query_styles.trim().is_empty() {
       styles.push(query_styles);
     }
   }
 
+  // Combined media queries are written after the single media queries, ordered
+  // by the position of each query.
+  let mut combined_queries: Vec<(Vec<usize>, &[String])> = queries_map
+    .keys()
+    .filter(|names| names.len() > 1)
+    .map(|names| {
+      let positions = names
+        .iter()
+        .filter_map(|name| media_queries.get_index_of(name))
+        .collect();
+
+      (positions, *names)
+    })
+    .collect();
+  combined_queries.sort();
+
+  for (_, names) in combined_queries {
+    let css_list = get_css_with_feature_queries(&queries_map[names], feature_queries);
+
+    styles.push(get_css_from_media_query(
+      &css_list,
+      &media_queries.get_query(names),
+    ));
+  }
+
   styles.join("\n\n")
 }
 
+/// The `supports` and `containerQueries` from the configuration.
+type FeatureQueries<'config> = (
+  &'config IndexMap<String, String>,
+  &'config IndexMap<String, ContainerQuery>,
+);
+
+/// Get the css for each class name. Class names with a feature query are
+/// grouped into `@supports` blocks after the other styles and container
+/// queries are nested within them.
+fn get_css_with_feature_queries(
+  class_name_list: &[&ClassName],
+  (supports, container_queries): &FeatureQueries,
+) -> Vec<String> {
+  let class_names_without_supports: Vec<&ClassName> = class_name_list
+    .iter()
+    .filter(|class_name| class_name.supports.is_none())
+    .copied()
+    .collect();
+  let mut css_list =
+    get_css_with_container_queries(&class_names_without_supports, container_queries);
+
+  for (query_name, feature_query) in supports.iter() {
+    let class_names: Vec<&ClassName> = class_name_list
+      .iter()
+      .filter(|class_name| class_name.supports.as_ref() == Some(query_name))
+      .copied()
+      .collect();
+
+    if class_names.is_empty() {
+      continue;
+    }
+
+    css_list.push(format!(
+      "@supports {} {{\n{}\n}}",
+      feature_query,
+      indent(
+        &get_css_with_container_queries(&class_names, container_queries).join("\n\n"),
+        INDENTATION
+      )
+    ));
+  }
+
+  css_list
+}
+
+/// Get the css for each class name. Class names with a container query are
+/// grouped into `@container` blocks after the other styles.
+fn get_css_with_container_queries(
+  class_name_list: &[&ClassName],
+  container_queries: &IndexMap<String, ContainerQuery>,
+) -> Vec<String> {
+  let mut css_list: Vec<String> = class_name_list
+    .iter()
+    .filter(|class_name| class_name.container_query.is_none())
+    .map(|class_name| class_name.get_css())
+    .collect();
+
+  for (query_name, container_query) in container_queries {
+    let container_css_list: Vec<String> = class_name_list
+      .iter()
+      .filter(|class_name| class_name.container_query.as_ref() == Some(query_name))
+      .map(|class_name| class_name.get_css())
+      .collect();
+
+    if container_css_list.is_empty() {
+      continue;
+    }
+
+    css_list.push(format!(
+      "@container {} {{\n{}\n}}",
+      container_query.get_query(),
+      indent(&container_css_list.join("\n\n"), INDENTATION)
+    ));
+  }
+
+  css_list
+}
+
 type CssVariableSelectors = IndexMap<String, Vec<String>>;
 type CssVariablesTuple = (
   CssVariableSelectors,
   IndexMap<String, CssVariableSelectors>,
   IndexMap<String, CssVariableSelectors>,
+  IndexMap<String, CssVariableSelectors>,
 );
 
+/// Group the declarations of the used css variables by selector, breakpoint,
+/// media query and theme.
+///
+/// Themes without a media query share the block of their selector so that each
+/// theme is declared once. Themes with a media query are grouped by the media
+/// query and declared after the selectors.
 fn create_css_variable_containers(
   config: &Config,
   css_variable_names: &IndexSet<String>,
@@ -196,6 +673,13 @@ fn create_css_variable_containers(
   let mut css_variable_selectors: CssVariableSelectors = IndexMap::new();
   let mut css_variable_breakpoints: IndexMap<String, CssVariableSelectors> = IndexMap::new();
   let mut css_variable_media_queries: IndexMap<String, CssVariableSelectors> = IndexMap::new();
+  let mut css_variable_themes: IndexMap<String, CssVariableSelectors> = IndexMap::new();
+  let theme_targets: IndexMap<&String, Vec<ThemeTarget>> = config
+    .user
+    .themes
+    .keys()
+    .map(|name| (name, config.user.get_theme_targets(name)))
+    .collect();
   println!("css_variable_names: {:?}", config.css_variables);
 
   for (name, populated_variable) in config.css_variables.iter() {
@@ -216,12 +700,25 @@ fn create_css_variable_containers(
     if let Some(media_queries) = &populated_variable.media_queries {
       update_nested_variable_container(name, media_queries, &mut css_variable_media_queries);
     }
+
+    for (theme, value) in populated_variable.themes.iter().flatten() {
+      for target in theme_targets.get(theme).into_iter().flatten() {
+        let selectors = IndexMap::from([(target.selector.clone(), value.clone())]);
+        let container = match &target.media_query {
+          Some(media_query) => css_variable_themes.entry(media_query.clone()).or_default(),
+          None => &mut css_variable_selectors,
+        };
+
+        update_css_variable_container(name, &selectors, container);
+      }
+    }
   }
 
   (
     css_variable_selectors,
     css_variable_breakpoints,
     css_variable_media_queries,
+    css_variable_themes,
   )
 }
 
@@ -274,17 +771,15 @@ fn get_css_variable_declarations(css_variable_selectors: &IndexMap<String, Vec<S
 fn get_css_from_breakpoints(
   css_list: &[String],
   named_breakpoint: &Option<String>,
-  css_value: &CssValue,
+  breakpoint_query: &str,
 ) -> String {
   let inner_styles = css_list.join("\n\n");
 
   match named_breakpoint {
     Some(_) => {
-      let min_width = css_value.get_string();
-
       format!(
-        "@media (min-width: {}) {{\n{}\n}}",
-        min_width,
+        "@media {} {{\n{}\n}}",
+        breakpoint_query,
         indent(&inner_styles, INDENTATION)
       )
     }
@@ -304,7 +799,218 @@ fn get_css_from_media_query(css_list: &[String], media_query: &str) -> String {
 
 #[cfg(test)]
 mod tests {
-  use crate::test_utils::test_css;
+  use super::*;
+  use crate::{config::ConfigError, test_utils::collect_classes, test_utils::test_css};
+
+  #[test]
+  fn css_variables_are_grouped_by_theme() -> Result<(), ConfigError> {
+    let config = Config::new(
+      r##"{
+        "extends": "default",
+        "themes": {
+          "light": { "default": true },
+          "dark": { "selector": "html.dark", "mediaQuery": "(prefers-color-scheme: dark)" },
+          "darkContrast": { "parent": "dark", "mediaQuery": "(prefers-contrast: more)" }
+        },
+        "colors": {
+          "surface": { "themes": { "light": "gray50", "dark": "gray900", "darkContrast": "black" } }
+        }
+      }"##,
+    )?;
+    let collector = collect_classes(&config, "import { c } from 'skribble-css';\nc.bg.$surface;");
+    insta::assert_snapshot!(generate_css(&config, &collector.get_class_names()));
+
+    Ok(())
+  }
+
+  #[test]
+  fn fractional_and_negative_scale_steps_can_be_used() -> Result<(), ConfigError> {
+    let config = Config::new(
+      r#"{
+        "extends": "default",
+        "merge": { "atoms": "override" },
+        "atoms": [{ "styleRules": ["mt"], "scale": { "start": -1, "end": 1, "step": 0.5, "multiplier": 0.5, "unit": "rem" } }]
+      }"#,
+    )?;
+    let collector = collect_classes(
+      &config,
+      "import { c } from 'skribble-css';\nc.mt.$0_5;\nc.mt.$_1;",
+    );
+
+    insta::assert_snapshot!(generate_css(&config, &collector.get_class_names()), @r###"
+    .mt\:\:\$0_5 {
+      margin-top: 0.25rem;
+    }
+
+    .mt\:\:\$_1 {
+      margin-top: -0.5rem;
+    }
+    "###);
+
+    Ok(())
+  }
+
+  #[test]
+  fn fluid_atoms_use_clamp() -> Result<(), ConfigError> {
+    let config = Config::new(
+      r#"{
+        "extends": "default",
+        "atoms": [{ "styleRules": ["fontSize"], "fluid": { "values": { "xl": ["1.25rem", "1.5rem"] } } }]
+      }"#,
+    )?;
+    let collector = collect_classes(
+      &config,
+      "import { c } from 'skribble-css';\nc.fontSize.$xl;",
+    );
+
+    insta::assert_snapshot!(generate_css(&config, &collector.get_class_names()), @r###"
+    .fontSize\:\:\$xl {
+      font-size: clamp(1.25rem, 1.0714rem + 0.4464vw, 1.5rem);
+    }
+    "###);
+
+    Ok(())
+  }
+
+  #[test]
+  fn namespaced_palette_colors_can_be_used() -> Result<(), ConfigError> {
+    let config = Config::new(
+      r#"{
+        "extends": "default",
+        "palette": ["tailwind", { "namespace": "oc", "palette": "openColor" }]
+      }"#,
+    )?;
+    let collector = collect_classes(
+      &config,
+      "import { c } from 'skribble-css';\nc.bg.$ocBlue500;",
+    );
+
+    insta::assert_snapshot!(generate_css(&config, &collector.get_class_names()), @r###"
+    .bg\:\:\$ocBlue500 {
+      --sk-bg-opacity: 1;
+      color: rgba(51, 154, 240, var(--sk-bg-opacity));
+    }
+    "###);
+
+    Ok(())
+  }
+
+  #[test]
+  fn font_faces_are_written_for_used_families() -> Result<(), ConfigError> {
+    let config = Config::new(
+      r#"{
+        "extends": "default",
+        "fontFaces": [
+          {
+            "family": "Inter",
+            "src": [{ "local": "Inter" }, { "url": "/fonts/inter.woff2", "format": "woff2" }],
+            "weight": "100 900",
+            "display": "swap",
+            "preload": true
+          },
+          { "family": "Unused", "src": ["/fonts/unused.woff2"], "preload": true }
+        ],
+        "atoms": [{ "styleRules": ["font"], "values": { "body": "Inter, sans-serif" } }]
+      }"#,
+    )?;
+    let collector = collect_classes(&config, "import { c } from 'skribble-css';\nc.font.$body;");
+    let class_names = collector.get_class_names();
+
+    insta::assert_snapshot!(generate_css(&config, &class_names), @r###"
+    @font-face {
+      font-family: "Inter";
+      src: local("Inter"), url("/fonts/inter.woff2") format("woff2");
+      font-weight: 100 900;
+      font-display: swap;
+    }
+
+    .font\:\:\$body {
+      font-family: Inter, sans-serif;
+    }
+    "###);
+    insta::assert_snapshot!(
+      generate_font_preloads(&config, &class_names),
+      @r###"<link rel="preload" href="/fonts/inter.woff2" as="font" type="font/woff2" crossorigin>"###
+    );
+
+    Ok(())
+  }
+
+  #[test]
+  fn base_styles_are_written_before_the_utilities() -> Result<(), ConfigError> {
+    let config = Config::new(
+      r#"{
+        "extends": "default",
+        "options": { "preflight": true },
+        "globalStyles": {
+          "main": { "max-width": "var(--container-max-width)" },
+          "@media print": { "main": { "max-width": "none" } }
+        }
+      }"#,
+    )?;
+    let collector = collect_classes(&config, "import { c } from 'skribble-css';\nc.px.$1;");
+
+    insta::assert_snapshot!(generate_css(&config, &collector.get_class_names()));
+
+    Ok(())
+  }
+
+  #[test]
+  fn base_styles_write_the_font_faces_and_keyframes_they_use() -> Result<(), ConfigError> {
+    let config = Config::new(
+      r#"{
+        "extends": "default",
+        "fontFaces": [{ "family": "Inter", "src": ["/fonts/inter.woff2"] }],
+        "globalStyles": {
+          "body": { "font-family": "Inter, sans-serif", "line-height": 1.5 },
+          ".loading": { "animation": "spin 1s linear infinite" }
+        }
+      }"#,
+    )?;
+
+    insta::assert_snapshot!(generate_css(&config, &[]), @r###"
+    @font-face {
+      font-family: "Inter";
+      src: url("/fonts/inter.woff2");
+    }
+
+    body {
+      font-family: Inter, sans-serif;
+      line-height: 1.5;
+    }
+
+    .loading {
+      animation: spin 1s linear infinite;
+    }
+
+    @keyframes spin {
+      to {
+        transform: rotate(360deg);
+      }
+    }
+    "###);
+
+    Ok(())
+  }
+
+  #[test]
+  fn layers_are_ordered_by_variant() -> Result<(), ConfigError> {
+    let config = Config::new(
+      r#"{
+        "extends": "default",
+        "options": { "layers": { "atoms": "utilities" } },
+        "globalStyles": { "main": { "max-width": "var(--container-max-width)" } }
+      }"#,
+    )?;
+    let collector = collect_classes(
+      &config,
+      "import { c } from 'skribble-css';\nc.md.px.$1;\nc.hover.px.$1;\nc.px.$1;\nc.$italic;",
+    );
+
+    insta::assert_snapshot!(generate_css(&config, &collector.get_class_names()));
+
+    Ok(())
+  }
 
   test_css!(generate_css_from_simplest_atoms: r#"
       import { c } from 'skribble-css';
@@ -323,6 +1029,47 @@ mod tests {
   c.md.print.px.$px;"#
   );
 
+  test_css!(max_and_range_breakpoints: r#"
+  import { c } from 'skribble-css';
+  c.mdOnly.px.$px;
+  c.maxSm.px.$1;
+  c.md.px.$2;
+  c.maxMd.px.$px;
+  c.xxlOnly.px.$1;"#
+  );
+
+  test_css!(container_queries: r#"
+  import { c } from 'skribble-css';
+  c.$queryContainer;
+  c.cqMd.px.$px;
+  c.px.$1;
+  c.md.cqSm.px.$2;
+  c.print.cqMd.px.$1;"#
+  );
+
+  test_css!(feature_queries: r#"
+  import { c } from 'skribble-css';
+  c.grid.display('grid');
+  c.display.$block;
+  c.md.backdrop.backdropBlur('4px');
+  c.print.grid.cqMd.display('grid');"#
+  );
+
+  test_css!(combined_media_queries: r#"
+  import { c } from 'skribble-css';
+  c.motionReduce.darkScheme.px.$px;
+  c.darkScheme.px.$1;
+  c.md.print.darkScheme.px.$2;
+  c.landscape.motionSafe.px.$1;"#
+  );
+
+  test_css!(keyframes_are_only_written_when_used: r#"
+  import { c } from 'skribble-css';
+  c.animation.$spin;
+  c.md.animation('ping 2s infinite');
+  c.animation.$none;"#
+  );
+
   test_css!(media_queries_css_variables: r#"
   import { c } from 'skribble-css';
   c.print.text.$media;"# );
@@ -0,0 +1,427 @@
+use indexmap::{IndexMap, IndexSet};
+use serde_json::{json, Map, Value};
+
+use crate::{
+  config::{
+    color_expression::ColorResolver,
+    color_utils::{get_hex_string, parse_rgb},
+    user::{Atom, CssValue, CssVariable, PopulatedCssVariable},
+    Config, ConfigError,
+  },
+  constants::ROOT_SELECTOR,
+};
+
+/// The key used for values which only apply to some selectors or themes.
+const EXTENSIONS_KEY: &str = "skribble";
+
+/// Generate [W3C Design Tokens](https://design-tokens.github.io/community-group/format/)
+/// from the resolved configuration.
+///
+/// The tokens are grouped into `palette`, `color`, `variable`, `breakpoint`
+/// and `atom`. Colors which use a palette name are aliases of the palette
+/// token and values for other selectors and themes are kept in
+/// `$extensions.skribble`. Color atoms are left out since they are made from
+/// the palette and colors.
+pub fn generate_design_tokens(config: &Config) -> Result<String, ConfigError> {
+  Ok(to_json(&create_design_tokens(config)?))
+}
+
+/// Generate the tokens from `generate_design_tokens` as a
+/// [Style Dictionary](https://amzn.github.io/style-dictionary/) tree which uses
+/// `value`, `type` and `comment` instead of the `$` prefixed properties.
+pub fn generate_style_dictionary(config: &Config) -> Result<String, ConfigError> {
+  Ok(to_json(&to_style_dictionary(create_design_tokens(config)?)))
+}
+
+fn to_json(value: &Value) -> String {
+  let mut json = serde_json::to_string_pretty(value).unwrap();
+  json.push('\n');
+
+  json
+}
+
+fn create_design_tokens(config: &Config) -> Result<Value, ConfigError> {
+  let palette = config.user.palette.to_map();
+  let colors = ColorResolver::new(&palette, &config.user.colors).resolve_colors()?;
+  let mut tokens = Map::new();
+
+  let mut palette_group = Map::new();
+  for (name, value) in palette.iter() {
+    palette_group.insert(
+      name.to_owned(),
+      json!({ "$type": "color", "$value": get_color(value) }),
+    );
+  }
+  tokens.insert("palette".into(), Value::Object(palette_group));
+
+  let mut color_group = Map::new();
+  for (name, color) in colors.iter() {
+    let get_value = |value: &CssValue| {
+      let value = value.get_string();
+
+      match palette.contains_key(&value) {
+        true => Value::String(format!("{{palette.{}}}", value)),
+        false => get_color(&value),
+      }
+    };
+
+    color_group.insert(name.to_owned(), create_token("color", color, get_value));
+  }
+  tokens.insert("color".into(), Value::Object(color_group));
+
+  let mut variable_group = Map::new();
+  for name in config.user.variables.keys() {
+    let populated = &config.css_variables[name];
+    let mut token = match get_default_value(populated) {
+      Some(value) => json!({ "$value": value.get_string() }),
+      // A variable without any value has nothing to export.
+      None => continue,
+    };
+    let mut extensions = Map::new();
+    let to_map = |selectors: &IndexMap<String, CssValue>| -> Map<String, Value> {
+      selectors
+        .iter()
+        .filter(|(selector, _)| *selector != ROOT_SELECTOR)
+        .map(|(selector, value)| (selector.to_owned(), Value::String(value.get_string())))
+        .collect()
+    };
+
+    let selectors = to_map(&populated.selectors);
+    if !selectors.is_empty() {
+      extensions.insert("selectors".into(), Value::Object(selectors));
+    }
+
+    if let Some(themes) = &populated.themes {
+      extensions.insert("themes".into(), Value::Object(to_map(themes)));
+    }
+
+    if let Some(breakpoints) = &populated.breakpoints {
+      let breakpoints: Map<String, Value> = breakpoints
+        .iter()
+        .filter_map(|(breakpoint, selectors)| {
+          selectors
+            .get(ROOT_SELECTOR)
+            .map(|value| (breakpoint.to_owned(), Value::String(value.get_string())))
+        })
+        .collect();
+
+      extensions.insert("breakpoints".into(), Value::Object(breakpoints));
+    }
+
+    if !extensions.is_empty() {
+      token["$extensions"] = json!({ EXTENSIONS_KEY: extensions });
+    }
+
+    if let Some(token_type) = get_token_type(&token["$value"]) {
+      token["$type"] = json!(token_type);
+    }
+
+    variable_group.insert(name.trim_start_matches('-').to_owned(), token);
+  }
+  tokens.insert("variable".into(), Value::Object(variable_group));
+
+  let mut breakpoint_group = Map::new();
+  for (name, value) in config.user.breakpoints.iter() {
+    breakpoint_group.insert(
+      name.to_owned(),
+      json!({ "$type": "dimension", "$value": value.get_string() }),
+    );
+  }
+  tokens.insert("breakpoint".into(), Value::Object(breakpoint_group));
+
+  let color_rules: IndexSet<&String> = config
+    .user
+    .atoms
+    .iter()
+    .flat_map(|atom| match atom {
+      Atom::Color(atom) => atom.style_rules.iter().collect(),
+      Atom::Value(_) | Atom::Scale(_) | Atom::Fluid(_) => vec![],
+    })
+    .collect();
+
+  let mut atom_group = Map::new();
+  for (rule, values) in config.atoms.iter() {
+    if color_rules.contains(rule) {
+      continue;
+    }
+
+    let mut rule_group = Map::new();
+
+    for (name, value) in values.iter() {
+      let value = Value::String(value.get_string());
+      let mut token = json!({ "$value": value });
+
+      if let Some(token_type) = get_token_type(&value) {
+        token["$type"] = json!(token_type);
+      }
+
+      rule_group.insert(name.to_owned(), token);
+    }
+
+    atom_group.insert(rule.to_owned(), Value::Object(rule_group));
+  }
+  tokens.insert("atom".into(), Value::Object(atom_group));
+
+  Ok(Value::Object(tokens))
+}
+
+/// Create the token for a css variable. The `:root` value or the value of the
+/// default theme is used as the `$value`.
+fn create_token(
+  token_type: &str,
+  variable: &CssVariable,
+  get_value: impl Fn(&CssValue) -> Value,
+) -> Value {
+  let object = match variable {
+    CssVariable::Value(value) => return json!({ "$type": token_type, "$value": get_value(value) }),
+    CssVariable::Object(object) => object,
+  };
+
+  let to_map = |selectors: &IndexMap<String, CssValue>| -> Map<String, Value> {
+    selectors
+      .iter()
+      .map(|(name, value)| (name.to_owned(), get_value(value)))
+      .collect()
+  };
+
+  let value = get_default_value(object);
+  let mut token = json!({ "$type": token_type, "$value": value.map(&get_value) });
+  let mut extensions = Map::new();
+
+  let selectors: Map<String, Value> = to_map(&object.selectors)
+    .into_iter()
+    .filter(|(selector, _)| selector != ROOT_SELECTOR)
+    .collect();
+
+  if !selectors.is_empty() {
+    extensions.insert("selectors".into(), Value::Object(selectors));
+  }
+
+  if let Some(themes) = &object.themes {
+    extensions.insert("themes".into(), Value::Object(to_map(themes)));
+  }
+
+  let nested = [
+    ("mediaQueries", &object.media_queries),
+    ("breakpoints", &object.breakpoints),
+  ];
+
+  for (key, container) in nested {
+    if let Some(container) = container {
+      let container: Map<String, Value> = container
+        .iter()
+        .map(|(name, selectors)| (name.to_owned(), Value::Object(to_map(selectors))))
+        .collect();
+
+      extensions.insert(key.into(), Value::Object(container));
+    }
+  }
+
+  if !extensions.is_empty() {
+    token["$extensions"] = json!({ EXTENSIONS_KEY: extensions });
+  }
+
+  token
+}
+
+/// The `:root` value, then the value of the default theme, then the first
+/// selector and finally the first value within a media query or breakpoint.
+fn get_default_value(variable: &PopulatedCssVariable) -> Option<&CssValue> {
+  let nested = [&variable.media_queries, &variable.breakpoints];
+
+  variable
+    .selectors
+    .get(ROOT_SELECTOR)
+    .or_else(|| {
+      variable
+        .themes
+        .as_ref()
+        .and_then(|themes| themes.values().next())
+    })
+    .or_else(|| variable.selectors.values().next())
+    .or_else(|| {
+      nested
+        .into_iter()
+        .flatten()
+        .flat_map(|container| container.values())
+        .find_map(|selectors| selectors.values().next())
+    })
+}
+
+/// Colors are written as hex when possible so they can be used outside of css.
+fn get_color(value: &str) -> Value {
+  match parse_rgb(value) {
+    Some(rgb) => Value::String(get_hex_string(&rgb, true)),
+    None => Value::String(value.to_owned()),
+  }
+}
+
+/// Infer the type of a css value which is only known to be a string.
+fn get_token_type(value: &Value) -> Option<&'static str> {
+  let value = value.as_str()?;
+
+  if parse_rgb(value).is_some() {
+    return Some("color");
+  }
+
+  let number_end = value
+    .find(|char: char| !(char.is_ascii_digit() || char == '.' || char == '-'))
+    .unwrap_or(value.len());
+  let (number, unit) = value.split_at(number_end);
+
+  if number.parse::<f64>().is_err() {
+    return None;
+  }
+
+  match unit {
+    "" => Some("number"),
+    "px" | "rem" => Some("dimension"),
+    "ms" | "s" => Some("duration"),
+    _ => None,
+  }
+}
+
+/// Replace the `$value`, `$type`, `$description` and `$extensions` properties
+/// with the names used by Style Dictionary.
+fn to_style_dictionary(value: Value) -> Value {
+  match value {
+    Value::Object(map) => Value::Object(
+      map
+        .into_iter()
+        .map(|(key, value)| {
+          let key = match key.as_str() {
+            "$value" => "value".to_owned(),
+            "$type" => "type".to_owned(),
+            "$description" => "comment".to_owned(),
+            "$extensions" => "extensions".to_owned(),
+            _ => key,
+          };
+
+          (key, to_style_dictionary(value))
+        })
+        .collect(),
+    ),
+    Value::String(alias) if alias.starts_with('{') && alias.ends_with('}') => {
+      Value::String(format!("{}.value}}", alias.trim_end_matches('}')))
+    }
+    other => other,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn create_tokens() -> Value {
+    let config = Config::new(
+      r##"{
+        "extends": "default",
+        "palette": ["tailwind", { "namespace": "brand", "palette": { "ink": "#111827" } }],
+        "colors": { "accent": "brandInk" }
+      }"##,
+    )
+    .unwrap();
+
+    serde_json::from_str(&generate_design_tokens(&config).unwrap()).unwrap()
+  }
+
+  #[test]
+  fn colors_alias_the_palette() {
+    let tokens = create_tokens();
+
+    pretty_assertions::assert_eq!(
+      tokens["palette"]["purple500"],
+      json!({ "$type": "color", "$value": "#a855f7" })
+    );
+    pretty_assertions::assert_eq!(tokens["palette"]["brandInk"]["$value"], json!("#111827"));
+    pretty_assertions::assert_eq!(
+      tokens["color"]["accent"]["$value"],
+      json!("{palette.brandInk}")
+    );
+    insta::assert_json_snapshot!(tokens["color"]["text"], @r###"
+    {
+      "$type": "color",
+      "$value": "{palette.gray900}",
+      "$extensions": {
+        "skribble": {
+          "selectors": {
+            "html.dark": "{palette.gray50}"
+          }
+        }
+      }
+    }
+    "###);
+  }
+
+  #[test]
+  fn exports_variables_breakpoints_and_atoms() {
+    let tokens = create_tokens();
+
+    insta::assert_json_snapshot!(tokens["variable"], @r###"
+    {
+      "sk-container-max-width": {
+        "$value": "none",
+        "$extensions": {
+          "skribble": {
+            "breakpoints": {
+              "sm": "640px",
+              "md": "768px",
+              "lg": "1024px",
+              "xl": "1280px",
+              "xxl": "1536px"
+            }
+          }
+        }
+      }
+    }
+    "###);
+    pretty_assertions::assert_eq!(
+      tokens["breakpoint"]["md"],
+      json!({ "$type": "dimension", "$value": "768px" })
+    );
+    pretty_assertions::assert_eq!(
+      tokens["atom"]["p"]["1"],
+      json!({ "$type": "dimension", "$value": "0.25rem" })
+    );
+    assert!(tokens["atom"].get("bg").is_none());
+  }
+
+  #[test]
+  fn exports_variables_without_a_root_value() {
+    let config = Config::new(
+      r#"{
+        "extends": "default",
+        "variables": { "--gap": { "selectors": { ".dense": "1rem", ".loose": "2rem" } } }
+      }"#,
+    )
+    .unwrap();
+    let tokens: Value = serde_json::from_str(&generate_design_tokens(&config).unwrap()).unwrap();
+
+    insta::assert_json_snapshot!(tokens["variable"]["sk-gap"], @r###"
+    {
+      "$value": "1rem",
+      "$extensions": {
+        "skribble": {
+          "selectors": {
+            ".dense": "1rem",
+            ".loose": "2rem"
+          }
+        }
+      },
+      "$type": "dimension"
+    }
+    "###);
+  }
+
+  #[test]
+  fn style_dictionary_uses_plain_properties() {
+    let config =
+      Config::new(r#"{ "extends": "default", "colors": { "accent": "purple500" } }"#).unwrap();
+    let tree: Value = serde_json::from_str(&generate_style_dictionary(&config).unwrap()).unwrap();
+
+    pretty_assertions::assert_eq!(
+      tree["color"]["accent"],
+      json!({ "type": "color", "value": "{palette.purple500.value}" })
+    );
+  }
+}
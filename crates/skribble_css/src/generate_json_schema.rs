@@ -0,0 +1,78 @@
+use schemars::schema_for;
+use serde_json::json;
+
+use crate::config::UserConfig;
+
+/// Generate the JSON Schema for the user configuration.
+///
+/// The schema is checked in as `crates/skribble_css/skribble.schema.json` so
+/// that editors can validate and autocomplete configuration files. It isn't
+/// part of any npm package, so reference it from the repository.
+///
+/// ```json
+/// {
+///   "$schema": "https://raw.githubusercontent.com/skribbledev/skribble/master/crates/skribble_css/skribble.schema.json"
+/// }
+/// ```
+pub fn generate_json_schema() -> String {
+  let mut schema = serde_json::to_value(schema_for!(UserConfig)).unwrap();
+
+  // Sections are only required when the configuration doesn't `extends`
+  // another one.
+  if let Some(required) = schema
+    .as_object_mut()
+    .and_then(|root| root.remove("required"))
+  {
+    schema["anyOf"] = json!([{ "required": ["extends"] }, { "required": required }]);
+  }
+
+  let mut json = serde_json::to_string_pretty(&schema).unwrap();
+  json.push('\n');
+
+  json
+}
+
+#[cfg(test)]
+mod tests {
+  use std::path::Path;
+
+  use super::*;
+
+  /// Run with `UPDATE_SCHEMA=1` to update the checked in schema.
+  #[test]
+  fn schema_is_up_to_date() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("skribble.schema.json");
+    let schema = generate_json_schema();
+
+    if std::env::var("UPDATE_SCHEMA").is_ok() {
+      std::fs::write(&path, &schema).unwrap();
+    }
+
+    let existing = std::fs::read_to_string(&path).unwrap_or_default();
+    assert!(
+      existing == schema,
+      "`skribble.schema.json` is out of date. Run the tests with `UPDATE_SCHEMA=1` to update it."
+    );
+  }
+
+  #[test]
+  fn describes_untagged_unions() {
+    let schema: serde_json::Value = serde_json::from_str(&generate_json_schema()).unwrap();
+
+    assert!(schema["definitions"]["Atom"]["anyOf"].is_array());
+    assert!(schema["definitions"]["StyleRule"]["anyOf"].is_array());
+    assert!(schema["definitions"]["CssVariable"]["anyOf"].is_array());
+    pretty_assertions::assert_eq!(
+      schema["properties"]["atoms"]["description"],
+      "All the atomic styles available."
+    );
+  }
+
+  #[test]
+  fn sections_are_optional_when_extending() {
+    let schema: serde_json::Value = serde_json::from_str(&generate_json_schema()).unwrap();
+
+    pretty_assertions::assert_eq!(schema["required"], serde_json::Value::Null);
+    pretty_assertions::assert_eq!(schema["anyOf"][0], json!({ "required": ["extends"] }));
+  }
+}
@@ -100,10 +100,8 @@ pub fn generate_typescript(config: &Config) -> String {
   let mut breakpoint_keys: Vec<String> = vec![];
 
   // Add breakpoints to the skribble_css_interface.
-  for (breakpoint, min_width) in config.user.breakpoints.iter() {
-    let comments = tsdoc_comments(
-      format!("@media (min-width: {}) {{\n  &\n}}", min_width.get_string()).as_str(),
-    );
+  for (breakpoint, query) in config.breakpoints.iter() {
+    let comments = tsdoc_comments(format!("@media {} {{\n  &\n}}", query).as_str());
 
     // Should check if the breakpoint name is safe (begins with a number, but for
     // now just wrap in a string)
@@ -126,29 +124,104 @@ pub fn generate_typescript(config: &Config) -> String {
   // => END BREAKPOINTS
 
   // => START MEDIA QUERIES
-  let mut media_query_keys: Vec<String> = vec![];
+  // Media queries from different groups can be combined so each group only
+  // omits its own keys.
+  let mut media_query_group_keys: Vec<String> = vec![];
 
-  // Add breakpoints to the skribble_css_interface.
-  for (media_query, value) in config.user.media_queries.iter() {
-    let comments = tsdoc_comments(format!("@media {} {{\n  &\n}}", value).as_str());
+  for (index, group) in config.user.media_queries.groups().into_iter().enumerate() {
+    let mut media_query_keys: Vec<String> = vec![];
+
+    for (media_query, value) in group.iter() {
+      let comments = tsdoc_comments(format!("@media {} {{\n  &\n}}", value).as_str());
+
+      skribble_css_interface.push(format!(
+        "{}'{}': WithCustomClassName<SkribbleMediaQueryCssGroup{}>;",
+        comments, media_query, index
+      ));
+      media_query_keys.push(format!("'{}'", media_query));
+    }
+
+    // A group can be empty.
+    if media_query_keys.is_empty() {
+      media_query_keys.push("never".to_owned());
+    }
+
+    let media_query_keys_name = format!("MediaQueryKeys{}", index);
+    types.push(format!(
+      "type {} = {};",
+      media_query_keys_name,
+      media_query_keys.join(" | ")
+    ));
+
+    types.push(format!(
+      "type SkribbleMediaQueryCssGroup{} = Omit<SkribbleCss, {} | {}>;",
+      index,
+      omitted_keys.join(" | "),
+      media_query_keys_name
+    ));
+    media_query_group_keys.push(media_query_keys_name);
+  }
+
+  omitted_keys.extend(media_query_group_keys);
+  // => END MEDIA QUERIES
+
+  // => START SUPPORTS
+  let mut supports_keys: Vec<String> = vec![];
+
+  for (supports, value) in config.user.supports.iter() {
+    let comments = tsdoc_comments(format!("@supports {} {{\n  &\n}}", value).as_str());
 
     skribble_css_interface.push(format!(
-      "{}'{}': WithCustomClassName<SkribbleMediaQueryCss>;",
-      comments, media_query
+      "{}'{}': WithCustomClassName<SkribbleSupportsCss>;",
+      comments, supports
     ));
-    media_query_keys.push(format!("'{}'", media_query));
+    supports_keys.push(format!("'{}'", supports));
+  }
+
+  // The section is optional so the keys can be empty.
+  if supports_keys.is_empty() {
+    supports_keys.push("never".to_owned());
   }
 
   types.push(format!(
-    "type MediaQueryKeys = {};",
-    media_query_keys.join(" | ")
+    "type SupportsKeys = {};",
+    supports_keys.join(" | ")
   ));
-  omitted_keys.push("MediaQueryKeys".to_owned());
+  omitted_keys.push("SupportsKeys".to_owned());
   types.push(format!(
-    "type SkribbleMediaQueryCss = Omit<SkribbleCss, {}>;",
+    "type SkribbleSupportsCss = Omit<SkribbleCss, {}>;",
     omitted_keys.join(" | ")
   ));
-  // => END MEDIA QUERIES
+  // => END SUPPORTS
+
+  // => START CONTAINER QUERIES
+  let mut container_query_keys: Vec<String> = vec![];
+
+  for (container_query, value) in config.user.container_queries.iter() {
+    let comments = tsdoc_comments(format!("@container {} {{\n  &\n}}", value.get_query()).as_str());
+
+    skribble_css_interface.push(format!(
+      "{}'{}': WithCustomClassName<SkribbleContainerQueryCss>;",
+      comments, container_query
+    ));
+    container_query_keys.push(format!("'{}'", container_query));
+  }
+
+  // The section is optional so the keys can be empty.
+  if container_query_keys.is_empty() {
+    container_query_keys.push("never".to_owned());
+  }
+
+  types.push(format!(
+    "type ContainerQueryKeys = {};",
+    container_query_keys.join(" | ")
+  ));
+  omitted_keys.push("ContainerQueryKeys".to_owned());
+  types.push(format!(
+    "type SkribbleContainerQueryCss = Omit<SkribbleCss, {}>;",
+    omitted_keys.join(" | ")
+  ));
+  // => END CONTAINER QUERIES
 
   // => START PARENT MODIFIERS
   let mut parent_modifier_keys: Vec<String> = vec![];
@@ -273,4 +346,38 @@ mod tests {
     let config = create_config(None).unwrap();
     insta::assert_snapshot!(generate_typescript(&config));
   }
+
+  #[test]
+  fn media_query_groups_only_omit_their_own_keys() {
+    let config = create_config(Some(
+      r#"{
+        "extends": "default",
+        "mediaQueries": [{ "screen": "screen" }, {}]
+      }"#
+        .into(),
+    ))
+    .unwrap();
+    let typescript = generate_typescript(&config);
+    let media_query_types: Vec<&str> = typescript
+      .lines()
+      .filter(|line| {
+        line.starts_with("type MediaQueryKeys") || line.starts_with("type SkribbleMediaQuery")
+      })
+      .collect();
+
+    insta::assert_snapshot!(media_query_types.join("\n"), @r###"
+    type MediaQueryKeys0 = 'print';
+    type SkribbleMediaQueryCssGroup0 = Omit<SkribbleCss, BreakpointKeys | MediaQueryKeys0>;
+    type MediaQueryKeys1 = 'portrait' | 'landscape';
+    type SkribbleMediaQueryCssGroup1 = Omit<SkribbleCss, BreakpointKeys | MediaQueryKeys1>;
+    type MediaQueryKeys2 = 'darkScheme' | 'lightScheme';
+    type SkribbleMediaQueryCssGroup2 = Omit<SkribbleCss, BreakpointKeys | MediaQueryKeys2>;
+    type MediaQueryKeys3 = 'motionSafe' | 'motionReduce';
+    type SkribbleMediaQueryCssGroup3 = Omit<SkribbleCss, BreakpointKeys | MediaQueryKeys3>;
+    type MediaQueryKeys4 = 'screen';
+    type SkribbleMediaQueryCssGroup4 = Omit<SkribbleCss, BreakpointKeys | MediaQueryKeys4>;
+    type MediaQueryKeys5 = never;
+    type SkribbleMediaQueryCssGroup5 = Omit<SkribbleCss, BreakpointKeys | MediaQueryKeys5>;
+    "###);
+  }
 }
@@ -1,10 +1,12 @@
 #![deny(clippy::all)]
 
-pub use crate::generate_css::generate_css;
+pub use crate::generate_css::{generate_css, generate_font_preloads};
 
 pub mod config;
 pub mod constants;
 mod generate_css;
+pub mod generate_design_tokens;
+pub mod generate_json_schema;
 pub mod generate_typescript;
 pub mod scanner;
 mod utils;
@@ -12,7 +12,10 @@ use swc_ecmascript::{
 use crate::{
   config::{user::CssValue, Config},
   constants::INDENTATION,
-  utils::{escape_css_string, get_css_variables_from_string, get_identifiers, indent},
+  utils::{
+    escape_css_string, get_css_variables_from_string, get_font_families_from_declarations,
+    get_identifiers, get_names_from_declarations, indent,
+  },
 };
 
 #[derive(Debug, Clone)]
@@ -22,13 +25,19 @@ pub enum Validity {
   Undefined,
 }
 
+/// Each tier is larger than any score from the tiers below it so that class
+/// names are ordered by breakpoint first. The `i64` representation keeps the
+/// higher tiers from overflowing on 32-bit targets.
+#[repr(i64)]
 enum ScoreMultiple {
   Value = 1,
   Atom = 100,
   Modifier = 10_000,
   ParentModifier = 100_000,
-  MediaQuery = 1_000_000,
-  Breakpoint = 10_000_000,
+  ContainerQuery = 1_000_000,
+  Supports = 10_000_000,
+  MediaQuery = 100_000_000,
+  Breakpoint = 1_000_000_000,
 }
 
 #[derive(Debug, Clone)]
@@ -115,8 +124,15 @@ pub struct ClassName<'config> {
   /// The name of the breakpoint provided if it exists.
   pub breakpoint: Option<String>,
 
-  /// The name of the media query.
-  pub media_query: Option<String>,
+  /// The names of the media queries, at most one from each group, in the order
+  /// of their groups.
+  pub media_queries: Vec<String>,
+
+  /// The name of the feature query.
+  pub supports: Option<String>,
+
+  /// The name of the container query.
+  pub container_query: Option<String>,
 
   /// The parent modifiers.
   pub parent_modifier: Option<String>,
@@ -153,7 +169,7 @@ pub struct ClassName<'config> {
   pub value: Option<CssValue>,
 
   /// This is used to order the class names.
-  pub score: isize,
+  pub score: i64,
 
   /// The configuration provided.
   #[readonly]
@@ -165,7 +181,9 @@ impl<'config> ClassName<'config> {
   pub fn new(config: &'config Config) -> Self {
     Self {
       breakpoint: None,
-      media_query: None,
+      media_queries: Vec::new(),
+      supports: None,
+      container_query: None,
       parent_modifier: None,
       modifiers: Vec::new(),
       shorthand: None,
@@ -256,10 +274,18 @@ impl<'config> ClassName<'config> {
       tokens.push(breakpoint.to_string());
     }
 
-    if let Some(media_query) = &self.media_query {
+    for media_query in self.media_queries.iter() {
       tokens.push(media_query.to_string());
     }
 
+    if let Some(supports) = &self.supports {
+      tokens.push(supports.to_string());
+    }
+
+    if let Some(container_query) = &self.container_query {
+      tokens.push(container_query.to_string());
+    }
+
     if let Some(parent_modifier) = &self.parent_modifier {
       tokens.push(parent_modifier.to_string());
     }
@@ -294,6 +320,8 @@ impl<'config> ClassName<'config> {
         let mut new_selectors = vec![];
 
         for modifier in modifiers {
+          let modifier = self.get_modifier_template(modifier);
+
           for selector in &selectors {
             new_selectors.push(modifier.replace('&', selector));
           }
@@ -311,6 +339,8 @@ impl<'config> ClassName<'config> {
         let mut new_selectors = vec![];
 
         for modifier in modifiers {
+          let modifier = self.get_modifier_template(modifier);
+
           for selector in &selectors {
             new_selectors.push(modifier.replace('&', selector));
           }
@@ -325,6 +355,16 @@ impl<'config> ClassName<'config> {
     selectors.join(", ")
   }
 
+  /// Get the template of a modifier or parent modifier, wrapped in `:where()`
+  /// when `whereSelectors` is enabled.
+  fn get_modifier_template(&self, modifier: &str) -> String {
+    if self.config.user.options.where_selectors {
+      wrap_modifier_in_where(modifier)
+    } else {
+      modifier.to_owned()
+    }
+  }
+
   pub fn is_valid(&self) -> bool {
     matches!(&self.validity, Validity::Valid)
   }
@@ -359,6 +399,27 @@ impl<'config> ClassName<'config> {
     get_css_variables_from_string(&self.get_style_declaration())
   }
 
+  /// The families of the configured `fontFaces` used by the declarations.
+  pub fn font_families(&self) -> IndexSet<String> {
+    get_font_families_from_declarations(
+      &self.get_style_declaration(),
+      self
+        .config
+        .user
+        .font_faces
+        .iter()
+        .map(|font_face| &font_face.family),
+    )
+  }
+
+  /// The names of the configured `keyframes` used by the declarations.
+  pub fn keyframes(&self) -> IndexSet<String> {
+    get_names_from_declarations(
+      &self.get_style_declaration(),
+      self.config.user.keyframes.keys(),
+    )
+  }
+
   pub fn get_css(&self) -> String {
     let selector = self.get_selector();
     let mut style_declaration = self.get_style_declaration();
@@ -380,7 +441,7 @@ impl<'config> ClassName<'config> {
     let token_string = token.to_string();
 
     // Handle the breakpoint case.
-    if self.config.user.breakpoints.keys().any(|v| v == token) {
+    if self.config.breakpoints.keys().any(|v| v == token) {
       match &self.breakpoint {
         Some(value) => {
           self.validity = Validity::Invalid;
@@ -393,7 +454,6 @@ impl<'config> ClassName<'config> {
           let mut increment = 0;
           if let Some(position) = self
             .config
-            .user
             .breakpoints
             .keys()
             .position(|name| name == token)
@@ -408,29 +468,70 @@ impl<'config> ClassName<'config> {
       return;
     }
 
-    if self.config.user.media_queries.keys().any(|v| v == token) {
-      match &self.media_query {
-        Some(value) => {
+    if let Some(group) = self.config.user.media_queries.get_group_of(token) {
+      let media_queries = &self.config.user.media_queries;
+      let existing = self
+        .media_queries
+        .iter()
+        .find(|name| media_queries.get_group_of(name) == Some(group));
+
+      match existing {
+        Some(_) => {
           self.validity = Validity::Invalid;
-          println!(
-            "Warning: Multiple media queries provided: {} and {}",
-            value, token
-          );
         }
 
         None => {
           let mut increment = 0;
-          if let Some(position) = self
-            .config
-            .user
-            .media_queries
-            .keys()
-            .position(|name| name == token)
-          {
+          if let Some(position) = media_queries.get_index_of(token) {
             increment = calculate_score_increment(ScoreMultiple::MediaQuery, position);
           }
           self.score += increment;
-          self.media_query = Some(token_string);
+
+          // Keep the media queries in the order of their groups.
+          let index = self
+            .media_queries
+            .iter()
+            .position(|name| media_queries.get_group_of(name) > Some(group))
+            .unwrap_or(self.media_queries.len());
+          self.media_queries.insert(index, token_string);
+        }
+      }
+
+      return;
+    }
+
+    if self.config.user.supports.contains_key(token) {
+      match &self.supports {
+        Some(_) => {
+          self.validity = Validity::Invalid;
+        }
+
+        None => {
+          let mut increment = 0;
+          if let Some(position) = self.config.user.supports.get_index_of(token) {
+            increment = calculate_score_increment(ScoreMultiple::Supports, position);
+          }
+          self.score += increment;
+          self.supports = Some(token_string);
+        }
+      }
+
+      return;
+    }
+
+    if self.config.user.container_queries.contains_key(token) {
+      match &self.container_query {
+        Some(_) => {
+          self.validity = Validity::Invalid;
+        }
+
+        None => {
+          let mut increment = 0;
+          if let Some(position) = self.config.user.container_queries.get_index_of(token) {
+            increment = calculate_score_increment(ScoreMultiple::ContainerQuery, position);
+          }
+          self.score += increment;
+          self.container_query = Some(token_string);
         }
       }
 
@@ -646,9 +747,47 @@ impl<'config> PartialOrd for ClassName<'_> {
   }
 }
 
-fn calculate_score_increment(multiple: ScoreMultiple, position: usize) -> isize {
-  let _multiple = multiple as isize;
-  _multiple * (position as isize)
+/// Wrap the parts of a modifier around the `&` in `:where()` so that they
+/// don't add to the specificity of the class name, e.g. `.dark &:hover`
+/// becomes `:where(.dark) &:where(:hover)`. Pseudo elements can't be used in
+/// `:where()` and are kept after it. Modifiers which select a different element
+/// than the `&`, e.g. `& > *`, are left unchanged.
+fn wrap_modifier_in_where(modifier: &str) -> String {
+  let (ancestor, compound) = match modifier.split_once('&') {
+    Some((_, compound)) if compound.contains('&') => return modifier.to_owned(),
+    Some(parts) => parts,
+    None => return modifier.to_owned(),
+  };
+
+  if compound.starts_with(|char: char| char.is_whitespace() || ">+~".contains(char)) {
+    return modifier.to_owned();
+  }
+
+  let ancestor = ancestor.trim_end();
+  let ancestor = match ancestor.chars().last() {
+    None => "".to_owned(),
+    Some(combinator) if "+~>".contains(combinator) => format!(
+      ":where({}) {} ",
+      ancestor.trim_end_matches(combinator).trim_end(),
+      combinator
+    ),
+    Some(_) => format!(":where({}) ", ancestor),
+  };
+  let (pseudo_classes, pseudo_element) = match compound.split_once("::") {
+    Some((pseudo_classes, pseudo_element)) => (pseudo_classes, format!("::{}", pseudo_element)),
+    None => (compound, "".to_owned()),
+  };
+  let pseudo_classes = match pseudo_classes {
+    "" => "".to_owned(),
+    pseudo_classes => format!(":where({})", pseudo_classes),
+  };
+
+  format!("{}&{}{}", ancestor, pseudo_classes, pseudo_element)
+}
+
+fn calculate_score_increment(multiple: ScoreMultiple, position: usize) -> i64 {
+  let _multiple = multiple as i64;
+  _multiple * (position as i64)
 }
 
 #[cfg(test)]
@@ -669,6 +808,70 @@ mod tests {
     assert!(class_name1 < class_name2);
   }
 
+  #[test]
+  fn derived_breakpoints_are_ordered_after_min_width() {
+    let config = create_config(None).unwrap();
+    let mut min_width = ClassName::new(&config);
+    let mut max_md = ClassName::new(&config);
+    let mut max_sm = ClassName::new(&config);
+    let mut md_only = ClassName::new(&config);
+
+    min_width.add_tokens(&["xxl", "p", "$px"]);
+    max_md.add_tokens(&["maxMd", "p", "$px"]);
+    max_sm.add_tokens(&["maxSm", "p", "$px"]);
+    md_only.add_tokens(&["mdOnly", "p", "$px"]);
+    pretty_assertions::assert_eq!(max_md.breakpoint, Some("maxMd".to_string()));
+    assert!(min_width < max_md);
+    assert!(max_md < max_sm);
+    assert!(max_sm < md_only);
+  }
+
+  #[test]
+  fn media_queries_combine_across_groups() {
+    let config = create_config(None).unwrap();
+    let mut combined = ClassName::new(&config);
+    let mut same_group = ClassName::new(&config);
+
+    combined.add_tokens(&["motionReduce", "darkScheme", "p", "$px"]);
+    same_group.add_tokens(&["darkScheme", "lightScheme", "p", "$px"]);
+    pretty_assertions::assert_eq!(combined.media_queries, vec!["darkScheme", "motionReduce"]);
+    insta::assert_snapshot!(&combined.get_selector(), @r".darkScheme\:motionReduce\:p\:\:\$px");
+    assert!(!same_group.is_valid());
+  }
+
+  #[test]
+  fn multiple_container_queries_are_invalid() {
+    let config = create_config(None).unwrap();
+    let mut class_name = ClassName::new(&config);
+
+    class_name.add_tokens(&["cqSm", "cqMd", "p", "$px"]);
+    assert!(class_name.is_invalid());
+  }
+
+  #[test]
+  fn multiple_feature_queries_are_invalid() {
+    let config = create_config(None).unwrap();
+    let mut class_name = ClassName::new(&config);
+
+    class_name.add_tokens(&["grid", "backdrop", "p", "$px"]);
+    assert!(class_name.is_invalid());
+  }
+
+  #[test]
+  fn where_selectors_keep_the_specificity_of_one_class() {
+    let config = create_config(Some(
+      r#"{ "extends": "default", "options": { "whereSelectors": true } }"#.into(),
+    ))
+    .unwrap();
+    let mut class_name = ClassName::new(&config);
+
+    class_name.add_tokens(&["dark", "sm", "focus", "p", "$px"]);
+    insta::assert_snapshot!(&class_name.get_selector(), @r":where(.dark) .sm\:dark\:focus\:p\:\:\$px:where(:focus)");
+    pretty_assertions::assert_eq!(wrap_modifier_in_where("&::placeholder"), "&::placeholder");
+    pretty_assertions::assert_eq!(wrap_modifier_in_where("ul > &"), ":where(ul) > &");
+    pretty_assertions::assert_eq!(wrap_modifier_in_where("& > *"), "& > *");
+  }
+
   #[test]
   fn can_add_tokens() {
     let config = create_config(None).unwrap();
@@ -688,6 +891,19 @@ mod tests {
     insta::assert_snapshot!(&class_name.get_selector(), @r".\$block");
   }
 
+  #[test]
+  fn get_selector_with_namespaced_values() {
+    let config = create_config(Some(
+      r#"{ "extends": "default", "palette": ["tailwind", { "namespace": "oc", "palette": "openColor" }] }"#
+        .into(),
+    ))
+    .unwrap();
+    let mut class_name = ClassName::new(&config);
+
+    class_name.add_tokens(&["bg", "$ocBlue500"]);
+    insta::assert_snapshot!(&class_name.get_selector(), @r".bg\:\:\$ocBlue500");
+  }
+
   #[test]
   fn get_selector_with_modifiers() {
     let config = create_config(None).unwrap();
@@ -5,7 +5,7 @@ use swc_ecmascript::{
 };
 
 use crate::{
-  config::Config,
+  config::{Config, ConfigError},
   constants::JSON_CONFIG,
   scanner::class_name_collector::{ClassNameCollector, ValidImport},
 };
@@ -54,7 +54,7 @@ pub(crate) fn get_selector(config: &Config, source: &str) -> String {
   class_names.first().unwrap().to_string()
 }
 
-pub(crate) fn create_config(json: Option<String>) -> Result<Config, serde_json::Error> {
+pub(crate) fn create_config(json: Option<String>) -> Result<Config, ConfigError> {
   let json_config = match json {
     Some(json) => json,
     None => JSON_CONFIG.to_string(),
@@ -103,7 +103,7 @@ macro_rules! test_css {
   ($test_name:ident : $source:expr $(, $macros:ident) *) => {
     #[test]
     $(#[$macros])*
-    fn $test_name() -> Result<(), serde_json::Error> {
+    fn $test_name() -> Result<(), crate::config::ConfigError> {
       let config = crate::test_utils::create_config(None)?;
       let mut class_name_collector = crate::test_utils::collect_classes(&config, indoc::indoc!{$source});
       class_name_collector.sort();
@@ -1,6 +1,6 @@
 use indexmap::IndexSet;
 
-use regex::Regex;
+use regex::{Captures, Regex};
 use swc_ecmascript::{
   ast::{Expr, Ident, Lit, MemberProp},
   utils::{id, Id},
@@ -8,6 +8,7 @@ use swc_ecmascript::{
 
 const ESCAPE_CSS_STRING_REGEX: &str = r#"(#|&|~|=|>|'|:|"|!|;|,|\.|\*|\+|\||\[|\]|\(|\)|/|\^|\$)"#;
 const CSS_VARIABLE_REGEX: &str = r#"var\((--[a-zA-Z0-9_\-]+)\)"#;
+const CSS_VARIABLE_REFERENCE_REGEX: &str = r#"var\(\s*(--[a-zA-Z0-9_\-]+)"#;
 
 /// Retrieve the css variables from the provided css value.
 pub(crate) fn get_css_variables_from_string(value: &str) -> IndexSet<String> {
@@ -17,6 +18,74 @@ pub(crate) fn get_css_variables_from_string(value: &str) -> IndexSet<String> {
     .collect()
 }
 
+/// Retrieve the names from `names` which are used as a value in the provided
+/// css declarations, e.g. `spin` in `animation: spin 1s linear infinite`.
+pub(crate) fn get_names_from_declarations<'a>(
+  declarations: &str,
+  names: impl IntoIterator<Item = &'a String>,
+) -> IndexSet<String> {
+  let words: IndexSet<&str> = declarations
+    .split(';')
+    .filter_map(|declaration| declaration.split_once(':'))
+    .flat_map(|(_, value)| {
+      value.split(|char: char| !(char.is_alphanumeric() || char == '-' || char == '_'))
+    })
+    .collect();
+
+  names
+    .into_iter()
+    .filter(|name| words.contains(name.as_str()))
+    .cloned()
+    .collect()
+}
+
+/// Retrieve the font families from `families` which are listed in the provided
+/// css declarations, e.g. `Inter` in `font-family: "Inter", sans-serif`.
+pub(crate) fn get_font_families_from_declarations<'a>(
+  declarations: &str,
+  families: impl IntoIterator<Item = &'a String>,
+) -> IndexSet<String> {
+  let listed: IndexSet<&str> = declarations
+    .split(';')
+    .filter_map(|declaration| declaration.split_once(':'))
+    .flat_map(|(_, value)| value.split(','))
+    .map(|family| {
+      family
+        .trim()
+        .trim_matches(|char| char == '"' || char == '\'')
+    })
+    .collect();
+
+  families
+    .into_iter()
+    .filter(|family| listed.contains(family.as_str()))
+    .cloned()
+    .collect()
+}
+
+/// Add the prefix to a css custom property name, e.g. `--text-opacity` becomes
+/// `--sk-text-opacity`. Names which aren't custom properties or are already
+/// prefixed are returned unchanged.
+pub(crate) fn prefix_css_variable(name: &str, prefix: &str) -> String {
+  let prefix = prefix.trim_matches('-');
+
+  match name.strip_prefix("--") {
+    Some(stripped) if !prefix.is_empty() && !stripped.starts_with(&format!("{}-", prefix)) => {
+      format!("--{}-{}", prefix, stripped)
+    }
+    _ => name.to_owned(),
+  }
+}
+
+/// Add the prefix to every css variable referenced with `var()` in the value.
+pub(crate) fn prefix_css_variables_in_string(value: &str, prefix: &str) -> String {
+  let re = Regex::new(CSS_VARIABLE_REFERENCE_REGEX).unwrap();
+  re.replace_all(value, |captures: &Captures| {
+    format!("var({}", prefix_css_variable(&captures[1], prefix))
+  })
+  .to_string()
+}
+
 pub(crate) fn escape_css_string(value: &str) -> String {
   let regex = Regex::new(ESCAPE_CSS_STRING_REGEX).unwrap();
   regex.replace_all(value, "\\$1").to_string()